@@ -1,9 +1,96 @@
 pub mod load {
 
     use std::{
-        collections::{hash_map::Entry, HashMap}, fs::File, io::{self, BufRead}, path::Path
+        collections::HashMap,
+        env,
+        ffi::OsString,
+        fs::File,
+        io::{self, BufRead},
+        path::{Path, PathBuf},
+        sync::OnceLock,
     };
 
+    use crate::{Codeword, IndicatorType, KeyedEntries, LookupError, WordIndex};
+
+    static DATA_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+    /// Sets the directory given on the command line (--data-dir), which is
+    /// searched before any of the default locations
+    pub fn set_data_dir(dir: &Path) {
+        let _ = DATA_DIR.set(dir.to_path_buf());
+    }
+
+    /// The directories searched for data files, in order of preference
+    pub fn data_dirs() -> Vec<PathBuf> {
+        data_dirs_from(|name| env::var_os(name))
+    }
+
+    /// As `data_dirs()`, but looking up environment variables with `var`
+    /// rather than in the process environment
+    pub fn data_dirs_from(var: impl Fn(&str) -> Option<OsString>) -> Vec<PathBuf> {
+        let var = |name: &str| var(name).filter(|value| !value.is_empty());
+        let mut dirs: Vec<PathBuf> = Vec::new();
+        if let Some(dir) = DATA_DIR.get() {
+            dirs.push(dir.clone());
+        }
+        if let Some(dir) = var("LOOKUP_DATA_DIR") {
+            dirs.push(PathBuf::from(dir));
+        }
+        // See the XDG base directory specification for the defaults here
+        if let Some(dir) = var("XDG_DATA_HOME") {
+            dirs.push(PathBuf::from(dir).join("lookup"));
+        } else if let Some(home) = var("HOME") {
+            dirs.push(PathBuf::from(home).join(".local/share/lookup"));
+        }
+        let system_dirs = var("XDG_DATA_DIRS")
+            .and_then(|dirs| dirs.into_string().ok())
+            .unwrap_or("/usr/local/share:/usr/share".to_string());
+        for dir in system_dirs.split(':').filter(|d| !d.is_empty()) {
+            dirs.push(PathBuf::from(dir).join("lookup"));
+        }
+        if let Ok(exe) = env::current_exe() {
+            if let Some(dir) = exe.parent() {
+                dirs.push(dir.to_path_buf());
+            }
+        }
+        dirs.push(PathBuf::from("."));
+        dirs
+    }
+
+    /// Finds a data file (word list, thesaurus etc.) by searching `data_dirs()`
+    pub fn data_path(file_name: &str) -> Result<PathBuf, LookupError> {
+        data_path_in(&data_dirs(), file_name)
+    }
+
+    /// Finds a data file by searching `dirs` in order
+    pub fn data_path_in(dirs: &[PathBuf], file_name: &str) -> Result<PathBuf, LookupError> {
+        for dir in dirs {
+            let path = dir.join(file_name);
+            if path.is_file() {
+                return Ok(path);
+            }
+        }
+        Err(LookupError::MissingDataFile {
+            file_name: file_name.to_string(),
+            searched: dirs.iter().map(|dir| dir.join(file_name)).collect(),
+        })
+    }
+
+    /// The obscurity level of the biggest word list installed, as not every
+    /// list ships (e.g. words_3.txt). If there are none it's 1, so that the
+    /// error for the missing list is about the everyday one.
+    pub fn default_obscurity() -> u8 {
+        default_obscurity_in(&data_dirs())
+    }
+
+    /// As `default_obscurity()`, for the word lists in `dirs`
+    pub fn default_obscurity_in(dirs: &[PathBuf]) -> u8 {
+        (1..=3)
+            .rev()
+            .find(|level| data_path_in(dirs, &format!("words_{}.txt", level)).is_ok())
+            .unwrap_or(1)
+    }
+
     fn read_lines<P>(filename: &P) -> io::Result<io::Lines<io::BufReader<File>>>
     where
         P: AsRef<Path>,
@@ -12,77 +99,400 @@ pub mod load {
         Ok(io::BufReader::new(file).lines())
     }
 
-    pub fn full_list(
+    // A data file which we found but then couldn't read
+    fn unreadable(path: &Path) -> LookupError {
+        LookupError::MissingDataFile {
+            file_name: path.display().to_string(),
+            searched: vec![path.to_path_buf()],
+        }
+    }
+
+    fn data_lines(file_name: &str) -> Result<impl Iterator<Item = String>, LookupError> {
+        let path = data_path(file_name)?;
+        let lines = read_lines(&path).map_err(|_| unreadable(&path))?;
+        Ok(lines.map_while(Result::ok))
+    }
+
+    pub fn full_list(index: &mut WordIndex, file_name: &str) -> Result<(), LookupError> {
+        let path = data_path(file_name)?;
+        if let Some(cached) = super::cache::read(&path) {
+            index.append(cached);
+            return Ok(());
+        }
+        let list = text_list(&path).map_err(|_| unreadable(&path))?;
+        // The cache is only an optimisation, so failing to write it
+        // (e.g. a read-only directory) is not an error
+        let _ = super::cache::write(&path, &list);
+        index.append(list);
+        Ok(())
+    }
+
+    pub fn text_list(path: &Path) -> io::Result<WordIndex> {
+        let mut list = WordIndex::new();
+        for word in read_lines(&path)?.map_while(Result::ok) {
+            list.add(word);
+        }
+        Ok(list)
+    }
+
+    pub fn thesaurus(results: &mut Vec<String>, word: &str) -> Result<(), LookupError> {
+        keyed_entries(results, "thesaurus.txt", word)
+    }
+
+    /// Cryptic crossword abbreviations of a word, e.g. "river" gives "r", "dee", "po"...
+    pub fn abbreviations(results: &mut Vec<String>, word: &str) -> Result<(), LookupError> {
+        keyed_entries(results, "abbreviations.txt", word)
+    }
+
+    /// The types of clue a word usually indicates, e.g. "backing" gives Reversal
+    pub fn indicators(results: &mut Vec<IndicatorType>, word: &str) -> Result<(), LookupError> {
+        let mut names = vec![];
+        keyed_entries(&mut names, "indicators.txt", word)?;
+        // Anything we don't know about is skipped, rather than making the whole file unusable
+        results.extend(
+            names
+                .iter()
+                .filter_map(|name| IndicatorType::from_name(name)),
+        );
+        Ok(())
+    }
+
+    /// The whole of a file like thesaurus.txt read at once, for when many
+    /// words are to be looked up
+    pub fn keyed_map(file_name: &str) -> Result<KeyedEntries, LookupError> {
+        let mut entries = KeyedEntries::new();
+        for line in data_lines(file_name)? {
+            let mut fields = line.split(',');
+            if let Some(word) = fields.next() {
+                let entry = entries.entry(word.to_string()).or_default();
+                entry.extend(fields.map(str::to_string));
+            }
+        }
+        Ok(entries)
+    }
+
+    /// The whole of indicators.txt, each word or phrase -> the types of clue it indicates
+    pub fn indicator_map() -> Result<HashMap<String, Vec<IndicatorType>>, LookupError> {
+        let entries = keyed_map("indicators.txt")?;
+        Ok(entries
+            .into_iter()
+            .map(|(word, names)| {
+                let kinds = names
+                    .iter()
+                    .filter_map(|name| IndicatorType::from_name(name))
+                    .collect();
+                (word, kinds)
+            })
+            .collect())
+    }
+
+    // Lines in these files are the word followed by its entries, e.g. "river,r,dee,po"
+    fn keyed_entries(
         results: &mut Vec<String>,
-        anagrams: &mut HashMap<String, Vec<usize>>,
         file_name: &str,
-        vec_index: &mut usize
-    ) {
-        if let Ok(lines) = read_lines(&file_name) {
-            for word in lines.map_while(Result::ok) {
-                results.push(word.clone());
-                let anagram = crate::sort_word(&word);
-                // Does this entry already exist? Add to vec if so, else create new vec
-                match anagrams.entry(anagram) {
-                    Entry::Vacant(e) => {
-                        e.insert(vec![*vec_index]);
-                    }
-                    Entry::Occupied(mut e) => {
-                        e.get_mut().push(*vec_index);
+        word: &str,
+    ) -> Result<(), LookupError> {
+        let search_string = &(word.to_string() + ",").to_ascii_lowercase();
+        for line in data_lines(file_name)? {
+            if line.starts_with(search_string) {
+                let words = line.split(",");
+                let mut first: bool = true;
+                for word in words {
+                    if first {
+                        first = false;
+                    } else {
+                        results.push(word.to_string());
                     }
                 }
-                *vec_index += 1;
             }
         }
+        Ok(())
     }
 
-    pub fn thesaurus(results: &mut Vec<String>, word: &str) {
-        let file_name = "./thesaurus.txt".to_string();
-        if let Ok(lines) = read_lines(&file_name) {
-            for line in lines.map_while(Result::ok) {
-                let search_string = &(word.to_string() + ",").to_ascii_lowercase();
-                if line.starts_with(search_string) {
-                    let words = line.split(",");
-                    let mut first: bool = true;
-                    for word in words {
-                        if first {
-                            first = false;
-                        } else {
-                            results.push(word.to_string());
-                        }
+    pub fn definitions(results: &mut Vec<String>, word: &str) -> Result<(), LookupError> {
+        let search_string = &(word.to_string() + "|");
+        for line in data_lines("definitions.txt")? {
+            if line.starts_with(search_string) {
+                let parts = line.split("|");
+                let mut first: bool = true;
+                for part in parts {
+                    if first {
+                        first = false;
+                    } else {
+                        results.push(part.to_string());
                     }
                 }
             }
         }
+        Ok(())
     }
 
-    pub fn definitions(results: &mut Vec<String>, word: &str) {
-        let file_name = "./definitions.txt".to_string();
-        if let Ok(lines) = read_lines(&file_name) {
-            for line in lines.map_while(Result::ok) {
-                let search_string = &(word.to_string() + "|");
-                if line.starts_with(search_string) {
-                    let parts = line.split("|");
-                    let mut first: bool = true;
-                    for part in parts {
-                        if first {
-                            first = false;
-                        } else {
-                            results.push(part.to_string());
-                        }
-                    }
-                }
+    /// Reads a codeword grid from a file (see `Codeword::parse()` for the format)
+    pub fn codeword(path: &Path) -> Result<Codeword, LookupError> {
+        let text = std::fs::read_to_string(path).map_err(|_| unreadable(path))?;
+        Codeword::parse(&text)
+    }
+
+    /// Reads just the words of the given length, for Wordle
+    pub fn wordle(
+        index: &mut WordIndex,
+        file_name: &str,
+        length: usize,
+    ) -> Result<(), LookupError> {
+        for word in data_lines(file_name)? {
+            if word.len() == length {
+                index.add(word);
+            }
+        }
+        Ok(())
+    }
+}
+
+pub mod cache {
+
+    // A word list is cached in a compact binary form next to the list itself
+    // (e.g. words_2.txt -> words_2.idx) so that startup doesn't need to re-read
+    // and re-sort every word. The header records the size, modification time
+    // and hash of the source list so a stale cache is rebuilt automatically.
+    //
+    // The words are stored once, one per line, followed by the anagram groups
+    // (each with the hash of its sorted letters) and length buckets as lists
+    // of indices into them. Indices within a list are in increasing order, so
+    // each is written as a variable length difference from the one before,
+    // which mostly takes a single byte. Letter counts and the phrase flags are
+    // quick to work out again on load.
+
+    use std::{
+        collections::HashMap,
+        fs,
+        io::{self, Write},
+        path::{Path, PathBuf},
+        time::UNIX_EPOCH,
+    };
+
+    use crate::{stable_hash as hash, WordIndex};
+
+    const MAGIC: &[u8; 4] = b"LKIX";
+    // Bump this whenever the layout, or the way entries are indexed, changes
+    const VERSION: u32 = 3;
+
+    struct Header {
+        size: u64,
+        mtime_secs: u64,
+        mtime_nanos: u32,
+        hash: u64,
+    }
+
+    pub fn cache_path(source: &Path) -> PathBuf {
+        source.with_extension("idx")
+    }
+
+    /// (Re)builds the cache for `source` regardless of whether the existing
+    /// one is up to date, returning the number of entries indexed
+    pub fn build(source: &Path) -> io::Result<usize> {
+        let list = super::load::text_list(source)?;
+        write(source, &list)?;
+        Ok(list.len())
+    }
+
+    /// Returns the cached index for `source`, or None if there is no cache
+    /// or it is out of date. Each entry's letter signature (and whether it's
+    /// a phrase) isn't stored, but worked out again from the words.
+    pub fn read(source: &Path) -> Option<WordIndex> {
+        let data = fs::read(cache_path(source)).ok()?;
+        let mut reader = Reader {
+            data: &data,
+            pos: 0,
+        };
+        if reader.bytes(4)? != MAGIC || reader.u32()? != VERSION {
+            return None;
+        }
+        let cached = Header {
+            size: reader.u64()?,
+            mtime_secs: reader.u64()?,
+            mtime_nanos: reader.u32()?,
+            hash: reader.u64()?,
+        };
+        let metadata = fs::metadata(source).ok()?;
+        if metadata.len() != cached.size {
+            return None;
+        }
+        let touched = modified(&metadata) != (cached.mtime_secs, cached.mtime_nanos);
+        if touched && hash(&fs::read(source).ok()?) != cached.hash {
+            // Same size but a different time: only stale if the content changed
+            return None;
+        }
+        let index = read_body(&mut reader)?;
+        if touched {
+            // Record the new time so we don't have to hash the list every run
+            let _ = write(source, &index);
+        }
+        Some(index)
+    }
+
+    pub fn write(source: &Path, index: &WordIndex) -> io::Result<()> {
+        let metadata = fs::metadata(source)?;
+        let (mtime_secs, mtime_nanos) = modified(&metadata);
+        let mut out: Vec<u8> = Vec::new();
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&VERSION.to_le_bytes());
+        out.extend_from_slice(&metadata.len().to_le_bytes());
+        out.extend_from_slice(&mtime_secs.to_le_bytes());
+        out.extend_from_slice(&mtime_nanos.to_le_bytes());
+        out.extend_from_slice(&hash(&fs::read(source)?).to_le_bytes());
+
+        put_u32(&mut out, index.words.len());
+        let text = index.words.join("\n");
+        put_u32(&mut out, text.len());
+        out.extend_from_slice(text.as_bytes());
+        put_u32(&mut out, index.anagrams.len());
+        for key in index.anagrams.keys() {
+            out.extend_from_slice(&key.to_le_bytes());
+            put_indices(&mut out, &index.anagram_indices(*key).collect::<Vec<_>>());
+        }
+        put_u32(&mut out, index.lengths.len());
+        for (length, indices) in &index.lengths {
+            put_varint(&mut out, *length);
+            put_indices(&mut out, indices);
+        }
+        // Write to a temporary file first so a concurrent reader never sees
+        // a partially written cache
+        let path = cache_path(source);
+        let tmp_path = path.with_extension("idx.tmp");
+        fs::File::create(&tmp_path)?.write_all(&out)?;
+        fs::rename(tmp_path, path)
+    }
+
+    fn read_body(reader: &mut Reader) -> Option<WordIndex> {
+        let count = reader.u32()? as usize;
+        let len = reader.u32()? as usize;
+        let text = std::str::from_utf8(reader.bytes(len)?).ok()?;
+        let words: Vec<String> = match count {
+            0 => Vec::new(),
+            _ => text.split('\n').map(str::to_string).collect(),
+        };
+        if words.len() != count {
+            return None;
+        }
+        let groups = reader.u32()? as usize;
+        let mut anagrams = HashMap::with_capacity(groups);
+        let mut next_anagram = vec![None; count];
+        let mut indices = Vec::new();
+        for _ in 0..groups {
+            let key = reader.u64()?;
+            reader.indices(count, &mut indices)?;
+            for pair in indices.windows(2) {
+                next_anagram[pair[0]] = Some(pair[1]);
             }
+            anagrams.insert(key, (*indices.first()?, *indices.last()?));
+        }
+        let buckets = reader.u32()? as usize;
+        let mut lengths = HashMap::with_capacity(buckets);
+        for _ in 0..buckets {
+            let length = reader.varint()?;
+            let mut indices = Vec::new();
+            reader.indices(count, &mut indices)?;
+            lengths.insert(length, indices);
+        }
+        Some(WordIndex::from_parts(
+            words,
+            anagrams,
+            next_anagram,
+            lengths,
+        ))
+    }
+
+    fn modified(metadata: &fs::Metadata) -> (u64, u32) {
+        metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|d| (d.as_secs(), d.subsec_nanos()))
+            .unwrap_or((0, 0))
+    }
+
+    fn put_u32(out: &mut Vec<u8>, value: usize) {
+        out.extend_from_slice(&(value as u32).to_le_bytes());
+    }
+
+    // Seven bits at a time, lowest first, with the top bit set on all but the last byte
+    fn put_varint(out: &mut Vec<u8>, mut value: usize) {
+        while value >= 0x80 {
+            out.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        out.push(value as u8);
+    }
+
+    // The number of indices, the first, then the difference from each to the next
+    fn put_indices(out: &mut Vec<u8>, indices: &[usize]) {
+        put_varint(out, indices.len());
+        let mut previous = 0;
+        for idx in indices {
+            put_varint(out, idx - previous);
+            previous = *idx;
         }
     }
 
-    pub fn wordle(results: &mut Vec<String>, file_name: &str) {
-        if let Ok(lines) = read_lines(&file_name) {
-            for word in lines.map_while(Result::ok) {
-                if word.len() == 5 {
-                    results.push(word.clone());
+    struct Reader<'a> {
+        data: &'a [u8],
+        pos: usize,
+    }
+
+    impl<'a> Reader<'a> {
+        fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+            let bytes = self.data.get(self.pos..self.pos + len)?;
+            self.pos += len;
+            Some(bytes)
+        }
+
+        fn u8(&mut self) -> Option<u8> {
+            Some(self.bytes(1)?[0])
+        }
+
+        fn u32(&mut self) -> Option<u32> {
+            Some(u32::from_le_bytes(self.bytes(4)?.try_into().ok()?))
+        }
+
+        fn u64(&mut self) -> Option<u64> {
+            Some(u64::from_le_bytes(self.bytes(8)?.try_into().ok()?))
+        }
+
+        fn varint(&mut self) -> Option<usize> {
+            let mut value: usize = 0;
+            for shift in (0..64).step_by(7) {
+                let byte = self.u8()?;
+                value |= ((byte & 0x7f) as usize) << shift;
+                if byte < 0x80 {
+                    return Some(value);
+                }
+            }
+            None
+        }
+
+        // Reads a list of indices into `indices`, rejecting any which are out
+        // of range (or not increasing) so that a corrupt cache can't cause a
+        // panic later on
+        fn indices(&mut self, count: usize, indices: &mut Vec<usize>) -> Option<()> {
+            let len = self.varint()?;
+            if len > count {
+                return None;
+            }
+            indices.clear();
+            indices.reserve(len);
+            let mut idx: usize = 0;
+            for i in 0..len {
+                let step = self.varint()?;
+                if i > 0 && step == 0 {
+                    return None;
+                }
+                idx = idx.checked_add(step)?;
+                if idx >= count {
+                    return None;
                 }
+                indices.push(idx);
             }
+            Some(())
         }
     }
 }
@@ -1,7 +1,9 @@
 use regex::Regex;
 use std::{
-    collections::{HashMap, HashSet},
-    process::exit,
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+    path::PathBuf,
+    rc::Rc,
 };
 
 use itertools::Itertools;
@@ -16,8 +18,11 @@ pub enum Action {
     Wordle,
     Spellingbee,
     Panagram,
+    Subanagram,
     Lookup,
+    LetterPattern,
     Anagram,
+    MultiAnagram,
     Jumble,
     Regex,
     Thesaurus,
@@ -25,6 +30,16 @@ pub enum Action {
     RegexWithThesaurus,
     RegularPatterns,
     Reverse,
+    Hidden,
+    RegularWords,
+    Codeword,
+    Cryptogram,
+    LetterBoxed,
+    Container,
+    Charade,
+    Abbreviation,
+    Indicator,
+    Clue,
     Remove,
 }
 
@@ -35,13 +50,303 @@ pub enum OutputType {
     Json,
 }
 
+/// The type of wordplay an indicator word in a cryptic clue usually signals,
+/// e.g. "mixed" signals an anagram
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum IndicatorType {
+    Anagram,
+    Reversal,
+    Hidden,
+    Container,
+    Homophone,
+    /// Alternate (or every third...) letters
+    Regular,
+}
+
+impl IndicatorType {
+    /// Parses the name used in indicators.txt, e.g. "reversal"
+    pub fn from_name(name: &str) -> Option<IndicatorType> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "anagram" => Some(IndicatorType::Anagram),
+            "reversal" => Some(IndicatorType::Reversal),
+            "hidden" => Some(IndicatorType::Hidden),
+            "container" => Some(IndicatorType::Container),
+            "homophone" => Some(IndicatorType::Homophone),
+            "regular" => Some(IndicatorType::Regular),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for IndicatorType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            IndicatorType::Anagram => "anagram",
+            IndicatorType::Reversal => "reversal",
+            IndicatorType::Hidden => "hidden",
+            IndicatorType::Container => "container",
+            IndicatorType::Homophone => "homophone",
+            IndicatorType::Regular => "regular",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Errors returned by the library. None of these are fatal to the caller: it's
+/// up to the caller (e.g. main.rs) to decide how to report them.
+#[derive(Debug, PartialEq)]
+pub enum LookupError {
+    /// A regex or wildcard pattern which couldn't be understood
+    BadPattern(String),
+    /// The search string was the wrong length for the type of search
+    WrongLength { expected: usize, actual: usize },
+    /// A "found" letter which doesn't appear in the source letters
+    LetterNotInSource(char),
+    /// A word list or other data file which couldn't be found in any of the
+    /// `searched` locations
+    MissingDataFile {
+        file_name: String,
+        searched: Vec<PathBuf>,
+    },
+}
+
+impl fmt::Display for LookupError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LookupError::BadPattern(msg) => write!(f, "invalid search pattern: {}", msg),
+            LookupError::WrongLength { expected, actual } => write!(
+                f,
+                "expected {} letters but the search string has {}",
+                expected, actual
+            ),
+            LookupError::LetterNotInSource(c) => write!(
+                f,
+                "you supplied a letter ({}) in the found (-f) or --minus option which \
+                does not appear in the source set of letters",
+                c
+            ),
+            LookupError::MissingDataFile {
+                file_name,
+                searched,
+            } => {
+                write!(f, "could not read data file {}, looked for:", file_name)?;
+                for path in searched {
+                    write!(f, "\n    {}", path.display())?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for LookupError {}
+
+/// The word and phrase lists held in memory, together with the lookup tables
+/// the various search modes need. Build it once (see `file::load::full_list`)
+/// and pass it to every search rather than re-reading the lists each time.
+#[derive(Default)]
+pub struct WordIndex {
+    words: Vec<String>,
+    // Hash of the sorted letters -> first and last indices into `words` with
+    // those letters, for anagram lookups. Keying on the hash keeps the table
+    // cheap to load from the cache; the (vanishingly rare) entry whose letters
+    // only share a hash is filtered out when looking it up.
+    anagrams: HashMap<u64, (usize, usize)>,
+    // The index of the next entry filed under the same hash, if any
+    next_anagram: Vec<Option<usize>>,
+    // Number of letters (ignoring spaces) -> indices into `words`
+    lengths: HashMap<usize, Vec<usize>>,
+    // True where the entry at the same index in `words` is a phrase
+    phrases: Vec<bool>,
+    // Letter counts of the entry at the same index in `words`
+    signatures: Vec<LetterCounts>,
+}
+
+// FNV-1a, which unlike the standard library's hasher gives the same result
+// on every run, so it can be written to the cache
+pub(crate) fn stable_hash(data: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in data {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+fn is_phrase(word: &str) -> bool {
+    word.contains(|c: char| c.is_whitespace() || c == '-')
+}
+
+/// Lines of a file such as thesaurus.txt, the word -> its entries (see
+/// `file::load::keyed_map`)
+pub type KeyedEntries = HashMap<String, Vec<String>>;
+
+/// The number of times each letter 'a' to 'z' appears in a word
+pub type LetterCounts = [u8; 26];
+
+pub fn letter_counts(word: &str) -> LetterCounts {
+    let mut counts: LetterCounts = [0; 26];
+    for c in word.chars() {
+        if c.is_ascii_alphabetic() {
+            counts[(c.to_ascii_lowercase() as u8 - b'a') as usize] += 1;
+        }
+    }
+    counts
+}
+
+impl WordIndex {
+    pub fn new() -> WordIndex {
+        WordIndex::default()
+    }
+
+    pub fn from_words(words: Vec<String>) -> WordIndex {
+        let mut index = WordIndex::new();
+        for word in words {
+            index.add(word);
+        }
+        index
+    }
+
+    // Rebuilds an index from its words and lookup tables (as read from the
+    // cache), working out the rest again
+    pub(crate) fn from_parts(
+        words: Vec<String>,
+        anagrams: HashMap<u64, (usize, usize)>,
+        next_anagram: Vec<Option<usize>>,
+        lengths: HashMap<usize, Vec<usize>>,
+    ) -> WordIndex {
+        WordIndex {
+            phrases: words.iter().map(|word| is_phrase(word)).collect(),
+            signatures: words.iter().map(|word| letter_counts(word)).collect(),
+            words,
+            anagrams,
+            next_anagram,
+            lengths,
+        }
+    }
+
+    pub fn add(&mut self, word: String) {
+        let idx = self.words.len();
+        let anagram = sort_word(&word);
+        self.lengths.entry(anagram.len()).or_default().push(idx);
+        self.link_anagram(stable_hash(anagram.as_bytes()), idx, idx);
+        self.next_anagram.push(None);
+        self.phrases.push(is_phrase(&word));
+        self.signatures.push(letter_counts(&word));
+        self.words.push(word);
+    }
+
+    /// Moves all the entries of `other` onto the end of this index
+    pub fn append(&mut self, other: WordIndex) {
+        if self.is_empty() {
+            *self = other;
+            return;
+        }
+        let offset = self.words.len();
+        self.next_anagram.extend(
+            other
+                .next_anagram
+                .into_iter()
+                .map(|next| next.map(|idx| idx + offset)),
+        );
+        for (key, (first, last)) in other.anagrams {
+            self.link_anagram(key, first + offset, last + offset);
+        }
+        for (length, indices) in other.lengths {
+            let entry = self.lengths.entry(length).or_default();
+            entry.extend(indices.into_iter().map(|idx| idx + offset));
+        }
+        self.words.extend(other.words);
+        self.phrases.extend(other.phrases);
+        self.signatures.extend(other.signatures);
+    }
+
+    // Adds the chain of entries from `first` to `last` onto the end of those
+    // already filed under `key`
+    fn link_anagram(&mut self, key: u64, first: usize, last: usize) {
+        match self.anagrams.get_mut(&key) {
+            Some((_, tail)) => {
+                self.next_anagram[*tail] = Some(first);
+                *tail = last;
+            }
+            None => {
+                self.anagrams.insert(key, (first, last));
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    pub fn words(&self) -> &[String] {
+        &self.words
+    }
+
+    pub fn is_phrase(&self, idx: usize) -> bool {
+        self.phrases[idx]
+    }
+
+    pub fn signature(&self, idx: usize) -> &LetterCounts {
+        &self.signatures[idx]
+    }
+
+    /// All entries whose sorted letters are exactly `sorted_letters`
+    pub fn anagrams<'a>(&'a self, sorted_letters: &'a str) -> impl Iterator<Item = &'a String> {
+        self.anagram_group(sorted_letters)
+            .filter(move |word| sort_word(word) == sorted_letters)
+    }
+
+    /// All entries which spell out exactly `letters` once any spaces (or
+    /// hyphens etc.) are removed, e.g. "jackolantern" finds "jack o lantern"
+    pub fn find<'a>(&'a self, letters: &'a str) -> impl Iterator<Item = &'a String> {
+        // Checking the letters in order also rules out any hash collisions
+        self.anagram_group(&sort_word(letters)).filter(move |word| {
+            word.chars()
+                .filter(|c| c.is_alphabetic())
+                .map(|c| c.to_ascii_lowercase())
+                .eq(letters.chars())
+        })
+    }
+
+    /// All entries with exactly `length` letters (spaces, hyphens and
+    /// apostrophes are not counted)
+    pub fn with_length(&self, length: usize) -> impl Iterator<Item = &String> {
+        self.indices(self.lengths.get(&length))
+    }
+
+    // Everything filed under the hash of `sorted_letters`
+    fn anagram_group(&self, sorted_letters: &str) -> impl Iterator<Item = &String> {
+        self.anagram_indices(stable_hash(sorted_letters.as_bytes()))
+            .map(|idx| &self.words[idx])
+    }
+
+    pub(crate) fn anagram_indices(&self, key: u64) -> impl Iterator<Item = usize> + '_ {
+        let first = self.anagrams.get(&key).map(|(first, _)| *first);
+        std::iter::successors(first, |idx| self.next_anagram[*idx])
+    }
+
+    fn indices<'a>(&'a self, indices: Option<&'a Vec<usize>>) -> impl Iterator<Item = &'a String> {
+        indices
+            .into_iter()
+            .flatten()
+            .map(move |idx| &self.words[*idx])
+    }
+}
+
 pub fn sort_word(word: &str) -> String {
-    // Strip all whitespace
-    let no_space: String = word.chars().filter(|c| !c.is_whitespace()).collect();
-    no_space.chars().sorted().collect::<String>()
+    // Strip all whitespace, hyphens, apostrophes etc.
+    word.chars()
+        .filter(|c| c.is_alphabetic())
+        .sorted()
+        .collect::<String>()
 }
 
-pub fn spellingbee(search_string: &str, word_list: &Vec<String>, debug: bool) -> Vec<String> {
+pub fn spellingbee(search_string: &str, index: &WordIndex, debug: bool) -> Vec<String> {
     let mut results: Vec<String> = Vec::new();
     let mut included_chars = "".to_string();
     let mut excluded_chars = "".to_string();
@@ -60,7 +365,7 @@ pub fn spellingbee(search_string: &str, word_list: &Vec<String>, debug: bool) ->
         println!("Included characters: [{}]", included_chars);
     }
     let min_len = 4;
-    for word in word_list {
+    for word in index.words() {
         let mut invalid = false;
         if debug {
             print!("\"{}\" : ", word);
@@ -80,211 +385,1933 @@ pub fn spellingbee(search_string: &str, word_list: &Vec<String>, debug: bool) ->
                 break;
             }
         }
-        if invalid {
-            continue;
+        if invalid {
+            continue;
+        }
+        // We now just have to ensure the word contains the mandatory letter
+        // which should be the first letter of the search string
+        let Some(c) = search_string.chars().next() else {
+            break;
+        };
+        if !word.contains(c) {
+            continue;
+        }
+        // If we get here, we haven't failed any checks, so it's a match
+        if debug {
+            println!(" *** match ***");
+        }
+        results.push(word.to_string());
+    }
+    results
+}
+
+/// The Spelling Bee ranks, with the percentage of the possible points needed
+/// for each
+pub const SPELLINGBEE_RANKS: [(&str, usize); 10] = [
+    ("Beginner", 0),
+    ("Good Start", 2),
+    ("Moving Up", 5),
+    ("Good", 8),
+    ("Solid", 15),
+    ("Nice", 25),
+    ("Great", 40),
+    ("Amazing", 50),
+    ("Genius", 70),
+    ("Queen Bee", 100),
+];
+
+/// A Spelling Bee puzzle: its letters (the centre one first) and answers
+#[derive(Debug, PartialEq)]
+pub struct SpellingBee {
+    pub letters: String,
+    pub words: Vec<String>,
+}
+
+impl SpellingBee {
+    pub fn new(search_string: &str, index: &WordIndex) -> SpellingBee {
+        SpellingBee {
+            letters: search_string.to_lowercase(),
+            words: spellingbee(&search_string.to_lowercase(), index, false),
+        }
+    }
+
+    /// Whether a word uses every letter
+    pub fn is_pangram(&self, word: &str) -> bool {
+        self.letters.chars().all(|c| word.contains(c))
+    }
+
+    /// The points for a word: 1 for four letters, otherwise one for each
+    /// letter, and 7 more for a pangram
+    pub fn score(&self, word: &str) -> usize {
+        let points = match word.len() {
+            4 => 1,
+            len => len,
+        };
+        match self.is_pangram(word) {
+            true => points + 7,
+            false => points,
+        }
+    }
+
+    pub fn total_score(&self) -> usize {
+        self.words.iter().map(|word| self.score(word)).sum()
+    }
+
+    pub fn pangrams(&self) -> Vec<String> {
+        self.words
+            .iter()
+            .filter(|word| self.is_pangram(word))
+            .cloned()
+            .collect()
+    }
+
+    /// The points needed for each rank, rounded to the nearest point
+    pub fn ranks(&self) -> Vec<(&'static str, usize)> {
+        let total = self.total_score();
+        SPELLINGBEE_RANKS
+            .iter()
+            .map(|(rank, percent)| (*rank, (total * percent + 50) / 100))
+            .collect()
+    }
+
+    /// The rank reached with the given points
+    pub fn rank(&self, points: usize) -> &'static str {
+        self.ranks()
+            .iter()
+            .rev()
+            .find(|(_, needed)| points >= *needed)
+            .map_or(SPELLINGBEE_RANKS[0].0, |(rank, _)| rank)
+    }
+
+    /// How many of the words not yet found start with each letter, by length
+    pub fn grid(&self, found: &[String]) -> BTreeMap<char, BTreeMap<usize, usize>> {
+        let mut grid: BTreeMap<char, BTreeMap<usize, usize>> = BTreeMap::new();
+        for word in self.unfound(found) {
+            if let Some(first) = word.chars().next() {
+                *grid
+                    .entry(first)
+                    .or_default()
+                    .entry(word.len())
+                    .or_default() += 1;
+            }
+        }
+        grid
+    }
+
+    /// How many of the words not yet found start with each pair of letters
+    pub fn two_letter_starts(&self, found: &[String]) -> BTreeMap<String, usize> {
+        let mut starts: BTreeMap<String, usize> = BTreeMap::new();
+        for word in self.unfound(found) {
+            *starts.entry(word.chars().take(2).collect()).or_default() += 1;
+        }
+        starts
+    }
+
+    fn unfound<'a>(&'a self, found: &'a [String]) -> impl Iterator<Item = &'a String> {
+        self.words.iter().filter(move |word| !found.contains(word))
+    }
+}
+
+/// Reads the sides of a Letter Boxed square, e.g. "ABC/DEF/GHI/JKL" (spaces
+/// or commas work too). No letter can be on more than one side.
+pub fn parse_letterboxed(square: &str) -> Result<Vec<String>, LookupError> {
+    let sides: Vec<String> = square
+        .split(['/', ' ', ','])
+        .filter(|side| !side.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    let letters: String = sides.concat();
+    if sides.len() < 2 || !letters.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(LookupError::BadPattern(format!(
+            "'{}' should be sides of letters like ABC/DEF/GHI/JKL",
+            square
+        )));
+    }
+    if let Some(c) = letters.chars().find(|c| letters.matches(*c).count() > 1) {
+        return Err(LookupError::BadPattern(format!(
+            "'{}' appears more than once in '{}'",
+            c, square
+        )));
+    }
+    Ok(sides)
+}
+
+/// Letter Boxed (NYT Puzzles): the words of three or more letters from the
+/// square, where each letter is on a different side to the one before
+pub fn letterboxed_words(sides: &[String], index: &WordIndex) -> Vec<String> {
+    let side_of = |c: char| sides.iter().position(|side| side.contains(c));
+    index
+        .words()
+        .iter()
+        .filter(|word| {
+            let sides: Vec<Option<usize>> = word.chars().map(side_of).collect();
+            word.len() >= 3
+                && sides.iter().all(Option::is_some)
+                && sides.windows(2).all(|pair| pair[0] != pair[1])
+        })
+        .cloned()
+        .collect()
+}
+
+/// The shortest chains of Letter Boxed words (each starting with the last
+/// letter of the one before) which use every letter, of up to `max_words`
+/// words. Chains with fewer letters come first.
+pub fn letterboxed(sides: &[String], index: &WordIndex, max_words: usize) -> Vec<Vec<String>> {
+    let letters: Vec<char> = sides.concat().chars().collect();
+    let all = (1u32 << letters.len()) - 1;
+    let mask = |word: &str| {
+        word.chars()
+            .filter_map(|c| letters.iter().position(|l| *l == c))
+            .fold(0u32, |mask, i| mask | 1 << i)
+    };
+    let words: Vec<(String, u32)> = letterboxed_words(sides, index)
+        .into_iter()
+        .map(|word| {
+            let m = mask(&word);
+            (word, m)
+        })
+        .collect();
+    let mut following: HashMap<char, Vec<usize>> = HashMap::new();
+    for (i, (word, _)) in words.iter().enumerate() {
+        if let Some(first) = word.chars().next() {
+            following.entry(first).or_default().push(i);
+        }
+    }
+    let mut search = LetterBoxedSearch {
+        words: &words,
+        following,
+        all,
+        chain: Vec::new(),
+        chains: Vec::new(),
+    };
+    for length in 1..=max_words {
+        search.add_words(0, length);
+        if !search.chains.is_empty() {
+            break;
+        }
+    }
+    let mut chains = search.chains;
+    chains.sort_by_key(|chain| (chain.iter().map(String::len).sum::<usize>(), chain.clone()));
+    chains
+}
+
+// Search state for letterboxed(): the words with the letters each uses (as
+// bits), the words starting with each letter, and the chains found so far
+struct LetterBoxedSearch<'a> {
+    words: &'a [(String, u32)],
+    following: HashMap<char, Vec<usize>>,
+    all: u32,
+    chain: Vec<usize>,
+    chains: Vec<Vec<String>>,
+}
+
+impl LetterBoxedSearch<'_> {
+    // Extends the chain with each word that can follow it, keeping chains of
+    // the given length that use every letter
+    fn add_words(&mut self, used: u32, length: usize) {
+        if self.chain.len() == length {
+            if used == self.all {
+                let chain = self
+                    .chain
+                    .iter()
+                    .map(|w| self.words[*w].0.clone())
+                    .collect();
+                self.chains.push(chain);
+            }
+            return;
+        }
+        let next: Vec<usize> = match self.chain.last() {
+            None => (0..self.words.len()).collect(),
+            Some(last) => {
+                let last = self.words[*last].0.chars().last().unwrap_or(' ');
+                self.following.get(&last).cloned().unwrap_or_default()
+            }
+        };
+        let is_last = self.chain.len() + 1 == length;
+        for word in next {
+            let used = used | self.words[word].1;
+            if is_last && used != self.all {
+                continue;
+            }
+            self.chain.push(word);
+            self.add_words(used, length);
+            self.chain.pop();
+        }
+    }
+}
+
+/// Panagram (Telegraph Puzzles): every word of four or more letters made from
+/// the grid, which must use the central letter (the first in `search_string`)
+pub fn panagram(search_string: &str, index: &WordIndex) -> Result<Vec<String>, LookupError> {
+    let min_len = 4;
+    if search_string.len() < min_len {
+        return Err(LookupError::WrongLength {
+            expected: min_len,
+            actual: search_string.len(),
+        });
+    }
+    let required_letter: String = search_string.chars().take(1).collect();
+    Ok(subanagrams(
+        search_string,
+        index,
+        min_len,
+        search_string.len(),
+        &required_letter,
+    ))
+}
+
+/// Every word which can be made from a subset of `letters`, using each letter
+/// at most once. A '?' in `letters` is a blank which can stand for any letter.
+/// Words must be `min_len` to `max_len` letters long, and contain all the
+/// letters in `mandatory`. Results are ordered by length.
+pub fn subanagrams(
+    letters: &str,
+    index: &WordIndex,
+    min_len: usize,
+    max_len: usize,
+    mandatory: &str,
+) -> Vec<String> {
+    let available = letter_counts(letters);
+    let blanks = letters.chars().filter(|c| *c == '?').count();
+    let mandatory = letter_counts(mandatory);
+    let max_len = max_len.min(available.iter().map(|c| *c as usize).sum::<usize>() + blanks);
+    let mut results: Vec<String> = Vec::new();
+    for len in min_len..=max_len {
+        let mut words: Vec<String> = Vec::new();
+        for idx in index.lengths.get(&len).into_iter().flatten() {
+            let word = &index.words[*idx];
+            let signature = index.signature(*idx);
+            if index.is_phrase(*idx)
+                || !word.chars().all(|c| c.is_ascii_alphabetic())
+                || !fits_letters(&mandatory, signature)
+            {
+                continue;
+            }
+            // Any letters we don't have must be made up with blanks
+            let missing: usize = signature
+                .iter()
+                .zip(&available)
+                .map(|(needed, have)| needed.saturating_sub(*have) as usize)
+                .sum();
+            if missing <= blanks {
+                words.push(word.to_string());
+            }
+        }
+        words.sort();
+        words.dedup();
+        results.append(&mut words);
+    }
+    results
+}
+
+pub fn anagram_search(search_string: &str, index: &WordIndex) -> Vec<String> {
+    let search_string = sort_word(search_string);
+    index
+        .anagrams(&search_string)
+        .map(|word| word.to_string())
+        .collect()
+}
+
+/// Takes the letters of `minus` out of `letters` (ignoring case, spaces and
+/// punctuation), e.g. "aldershotcafedemolition" minus "aldershotdemolition"
+/// leaves "cafe". Every letter removed must be there to remove.
+pub fn remove_letters(letters: &str, minus: &str) -> Result<String, LookupError> {
+    let mut remaining: String = letters
+        .chars()
+        .filter(|c| c.is_ascii_alphabetic())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    for c in minus.chars().filter(|c| c.is_ascii_alphabetic()) {
+        let c = c.to_ascii_lowercase();
+        if let Some(pos) = remaining.find(c) {
+            remaining.remove(pos);
+        } else {
+            return Err(LookupError::LetterNotInSource(c));
+        }
+    }
+    Ok(remaining)
+}
+
+/// Parses a crossword enumeration such as "11,4" or "(5,3-4)" into word
+/// lengths. Apostrophes don't split words, so "(2'1,4)" gives [3, 4].
+pub fn parse_enumeration(enumeration: &str) -> Result<Vec<usize>, LookupError> {
+    let bad = || LookupError::BadPattern(format!("invalid enumeration '{}'", enumeration));
+    enumeration
+        .trim_matches(|c| c == '(' || c == ')')
+        .split([',', '-', ' ', '/'])
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut len = 0;
+            for piece in part.split('\'') {
+                len += piece
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|len| *len > 0)
+                    .ok_or_else(bad)?;
+            }
+            Ok(len)
+        })
+        .collect()
+}
+
+/// True if the words of `word` have the lengths given by `enumeration`.
+/// Hyphenated words count as separate words, as they do in enumerations.
+pub fn matches_enumeration(word: &str, enumeration: &[usize]) -> bool {
+    word.split(|c: char| c.is_whitespace() || c == '-')
+        .filter(|w| !w.is_empty())
+        .map(|w| w.chars().filter(|c| c.is_alphabetic()).count())
+        .eq(enumeration.iter().copied())
+}
+
+/// Gives the crossword enumeration of a word or phrase, e.g. "a dead duck"
+/// gives "1,4,4", "jack-in-the-box" gives "4-2-3-3" and "o'clock" "1'5"
+pub fn enumeration(word: &str) -> String {
+    let mut result = String::new();
+    let mut len = 0;
+    for c in word.chars() {
+        let separator = match c {
+            c if c.is_alphabetic() => {
+                len += 1;
+                continue;
+            }
+            '-' | '\'' => c,
+            c if c.is_whitespace() => ',',
+            _ => continue,
+        };
+        if len > 0 {
+            result += &format!("{}{}", len, separator);
+            len = 0;
+        }
+    }
+    result + &len.to_string()
+}
+
+/// Turns a crossword enumeration in the search string into word breaks, e.g.
+/// "(5,3-4)" gives "5 3-4", which expand_numbers() then turns into dots
+pub fn expand_enumeration(search_string: &str) -> String {
+    search_string
+        .chars()
+        .filter(|c| *c != '(' && *c != ')')
+        .map(|c| if c == ',' { ' ' } else { c })
+        .collect()
+}
+
+/// True if every letter in `letters` is available in `available`
+pub fn fits_letters(letters: &LetterCounts, available: &LetterCounts) -> bool {
+    letters.iter().zip(available).all(|(a, b)| a <= b)
+}
+
+fn subtract_letters(available: &LetterCounts, letters: &LetterCounts) -> LetterCounts {
+    let mut remaining = *available;
+    for (r, l) in remaining.iter_mut().zip(letters) {
+        *r -= l;
+    }
+    remaining
+}
+
+fn matches_found(word: &str, found: &str) -> bool {
+    word.len() == found.len()
+        && word
+            .chars()
+            .zip(found.chars())
+            .all(|(w, f)| f == '.' || f == '_' || f == w)
+}
+
+/// Multi-word anagram solver: finds every combination of dictionary words
+/// which uses exactly the letters of `search_string`.
+/// `enumeration` gives the answer's word lengths (e.g. [11, 4]), and `found`
+/// any letters already known (e.g. "b.a.......g/...e", which also implies the
+/// enumeration if none is given). With no enumeration, combinations of up to
+/// `max_words` words of two or more letters are returned.
+pub fn multi_anagram(
+    search_string: &str,
+    index: &WordIndex,
+    enumeration: &[usize],
+    found: &str,
+    max_words: usize,
+) -> Result<Vec<String>, LookupError> {
+    let target = letter_counts(search_string);
+    let total: usize = target.iter().map(|c| *c as usize).sum();
+    let found = expand_numbers(&found.to_ascii_lowercase());
+    // The found letters are lined up with the answer by position
+    if !found.is_ascii() {
+        return Err(LookupError::BadPattern(format!(
+            "found letters '{}' should only use the letters a to z",
+            found
+        )));
+    }
+    let mut enumeration = enumeration.to_vec();
+    if found.contains('/') {
+        let found_enumeration: Vec<usize> = found.split('/').map(|w| w.len()).collect();
+        if enumeration.is_empty() {
+            enumeration = found_enumeration;
+        } else if enumeration != found_enumeration {
+            return Err(LookupError::BadPattern(
+                "found letters don't fit the enumeration".to_string(),
+            ));
+        }
+    }
+    if !enumeration.is_empty() && enumeration.iter().sum::<usize>() != total {
+        return Err(LookupError::WrongLength {
+            expected: enumeration.iter().sum(),
+            actual: total,
+        });
+    }
+    // The known letters without word breaks, padded out to the full length
+    let mut found_letters = found.replace('/', "");
+    if found_letters.len() > total {
+        return Err(LookupError::WrongLength {
+            expected: total,
+            actual: found_letters.len(),
+        });
+    }
+    while found_letters.len() < total {
+        found_letters.push('.');
+    }
+
+    // Only words which could be part of an answer are worth considering
+    let candidates: Vec<usize> = (0..index.len())
+        .filter(|idx| {
+            !index.is_phrase(*idx)
+                && index.words[*idx].chars().all(|c| c.is_ascii_alphabetic())
+                && fits_letters(index.signature(*idx), &target)
+        })
+        .collect();
+
+    let mut results: HashSet<String> = HashSet::new();
+    let mut chosen: Vec<usize> = Vec::new();
+    if enumeration.is_empty() {
+        let candidates: Vec<usize> = candidates
+            .into_iter()
+            .filter(|idx| index.words[*idx].len() > 1)
+            .collect();
+        combine_words(
+            index,
+            &candidates,
+            &target,
+            max_words,
+            &mut chosen,
+            &mut results,
+        );
+        results.retain(|answer| matches_found(&answer.replace(' ', ""), &found_letters));
+    } else {
+        let mut slots: Vec<(usize, &str)> = Vec::new();
+        let mut pos = 0;
+        for len in &enumeration {
+            slots.push((*len, &found_letters[pos..pos + len]));
+            pos += len;
+        }
+        fill_slots(
+            index,
+            &slots,
+            &candidates,
+            &target,
+            &mut chosen,
+            &mut results,
+        );
+    }
+    // Phrases in the list which are an exact anagram are answers too
+    for phrase in anagram_search(search_string, index) {
+        if (enumeration.is_empty() || matches_enumeration(&phrase, &enumeration))
+            && matches_found(&phrase.replace(' ', ""), &found_letters)
+        {
+            results.insert(phrase.to_lowercase());
+        }
+    }
+    Ok(results.into_iter().collect())
+}
+
+fn add_answer(index: &WordIndex, chosen: &[usize], results: &mut HashSet<String>) {
+    let words: Vec<&str> = chosen
+        .iter()
+        .map(|idx| index.words[*idx].as_str())
+        .collect();
+    results.insert(words.join(" ").to_lowercase());
+}
+
+// Adds every combination of `candidates` (in index order, so that each
+// combination is only found once) which uses exactly the `remaining` letters
+fn combine_words(
+    index: &WordIndex,
+    candidates: &[usize],
+    remaining: &LetterCounts,
+    max_words: usize,
+    chosen: &mut Vec<usize>,
+    results: &mut HashSet<String>,
+) {
+    if remaining.iter().all(|c| *c == 0) {
+        add_answer(index, chosen, results);
+        return;
+    }
+    if chosen.len() == max_words {
+        return;
+    }
+    if chosen.len() + 1 == max_words {
+        // Only room for one more word, so it must be an exact anagram of
+        // what's left, which we can look up directly
+        let key: String = (b'a'..=b'z')
+            .zip(remaining)
+            .flat_map(|(c, count)| std::iter::repeat_n(c as char, *count as usize))
+            .collect();
+        for idx in index.anagram_indices(stable_hash(key.as_bytes())) {
+            if index.signature(idx) == remaining && candidates.binary_search(&idx).is_ok() {
+                chosen.push(idx);
+                add_answer(index, chosen, results);
+                chosen.pop();
+            }
+        }
+        return;
+    }
+    for (pos, idx) in candidates.iter().enumerate() {
+        let left = subtract_letters(remaining, index.signature(*idx));
+        // Later words can only come from the candidates which still fit
+        let next: Vec<usize> = candidates[pos..]
+            .iter()
+            .filter(|next| fits_letters(index.signature(**next), &left))
+            .copied()
+            .collect();
+        chosen.push(*idx);
+        combine_words(index, &next, &left, max_words, chosen, results);
+        chosen.pop();
+    }
+}
+
+// Fills each (length, found letters) slot in turn with a candidate word
+// using the `remaining` letters
+fn fill_slots(
+    index: &WordIndex,
+    slots: &[(usize, &str)],
+    candidates: &[usize],
+    remaining: &LetterCounts,
+    chosen: &mut Vec<usize>,
+    results: &mut HashSet<String>,
+) {
+    let Some(((len, found), rest)) = slots.split_first() else {
+        add_answer(index, chosen, results);
+        return;
+    };
+    for idx in candidates {
+        let word = &index.words[*idx];
+        if word.len() != *len
+            || !fits_letters(index.signature(*idx), remaining)
+            || !matches_found(&word.to_lowercase(), found)
+        {
+            continue;
+        }
+        let left = subtract_letters(remaining, index.signature(*idx));
+        let next: Vec<usize> = candidates
+            .iter()
+            .filter(|next| fits_letters(index.signature(**next), &left))
+            .copied()
+            .collect();
+        chosen.push(*idx);
+        fill_slots(index, rest, &next, &left, chosen, results);
+        chosen.pop();
+    }
+}
+
+pub fn lookup(
+    search_string: &str,
+    index: &WordIndex,
+    exclude: &str,
+) -> Result<Vec<String>, LookupError> {
+    let pattern = Pattern::compile(search_string)?;
+    let mut results: HashSet<String> = HashSet::new();
+    // Without a wildcard we only need to look at words of the right length
+    let candidates: Vec<&String> = match pattern.length() {
+        Some(length) => index.with_length(length).collect(),
+        None => index.words().iter().collect(),
+    };
+    for word in candidates {
+        if pattern.matches(word, exclude) {
+            results.insert(word.to_string());
+        }
+    }
+    Ok(results.into_iter().collect())
+}
+
+// Word breaks and the like in search patterns
+fn is_separator(c: char) -> bool {
+    c == '/' || c == ' ' || c == '-' || c == '\''
+}
+
+const VOWELS: &str = "aeiou";
+
+// The number of letters a pattern or found string stands for, where a class
+// like "[aeiou]" is a single letter and word breaks aren't letters
+fn pattern_letters(pattern: &str) -> usize {
+    let mut count = 0;
+    let mut in_class = false;
+    for c in pattern.chars() {
+        match c {
+            '[' => in_class = true,
+            ']' => {
+                in_class = false;
+                count += 1
+            }
+            c if !in_class && !is_separator(c) => count += 1,
+            _ => {}
+        }
+    }
+    count
+}
+
+/// A lookup search string compiled for matching against words, e.g. "c_mp_t_r"
+/// or "s%es". See `Pattern::compile()` for the syntax.
+#[derive(Debug, PartialEq)]
+pub struct Pattern {
+    tokens: Vec<Token>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Letter(char),
+    /// One of these letters, or with `negated` any letter but these
+    Class {
+        letters: String,
+        negated: bool,
+    },
+    AnyLetter,
+    AnyRun,
+    WordBreak,
+    Apostrophe,
+}
+
+impl Pattern {
+    /// Compiles a lookup pattern, where '_' or '.' is any letter and '%' is
+    /// any number of letters (or none), spaces included, anywhere in the
+    /// pattern. "[aeiou]" is any one of those letters and "[^s]" any letter
+    /// but s, with '@' short for any vowel and '#' any consonant.
+    /// A space, '/' or hyphen must be a word break (a space or hyphen) in the
+    /// word, as the lists have hyphens as spaces, and apostrophes needn't
+    /// match as the lists leave them out.
+    pub fn compile(pattern: &str) -> Result<Pattern, LookupError> {
+        let bad = |problem: &str| LookupError::BadPattern(format!("{} in '{}'", problem, pattern));
+        let mut tokens: Vec<Token> = Vec::new();
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            let token = match c {
+                '[' => {
+                    let mut letters = String::new();
+                    loop {
+                        match chars.next() {
+                            Some(']') => break,
+                            Some(c) if c.is_alphabetic() || (c == '^' && letters.is_empty()) => {
+                                letters.push(c)
+                            }
+                            Some(c) => return Err(bad(&format!("unexpected '{}'", c))),
+                            None => return Err(bad("missing ']'")),
+                        }
+                    }
+                    let negated = letters.starts_with('^');
+                    if negated {
+                        letters.remove(0);
+                    }
+                    if letters.is_empty() {
+                        return Err(bad("empty '[]'"));
+                    }
+                    Token::Class { letters, negated }
+                }
+                '@' => Token::Class {
+                    letters: VOWELS.to_string(),
+                    negated: false,
+                },
+                '#' => Token::Class {
+                    letters: VOWELS.to_string(),
+                    negated: true,
+                },
+                '_' | '.' => Token::AnyLetter,
+                '%' => Token::AnyRun,
+                '/' | ' ' | '-' => Token::WordBreak,
+                '\'' => Token::Apostrophe,
+                c if c.is_alphabetic() => Token::Letter(c),
+                c => return Err(bad(&format!("unexpected '{}'", c))),
+            };
+            // "%%" is no different to "%", but would be slower
+            if token == Token::AnyRun && tokens.last() == Some(&Token::AnyRun) {
+                continue;
+            }
+            tokens.push(token);
+        }
+        Ok(Pattern { tokens })
+    }
+
+    /// The number of letters in every matching word, or None if it varies
+    pub fn length(&self) -> Option<usize> {
+        let mut length = 0;
+        for token in &self.tokens {
+            match token {
+                Token::Letter(_) | Token::Class { .. } | Token::AnyLetter => length += 1,
+                Token::AnyRun => return None,
+                Token::WordBreak | Token::Apostrophe => {}
+            }
+        }
+        Some(length)
+    }
+
+    /// True if `word` matches. Letters in `exclude` can only be matched by the
+    /// same letter in the pattern (or a class listing it), not by a wildcard.
+    pub fn matches(&self, word: &str, exclude: &str) -> bool {
+        let word: Vec<char> = word.chars().collect();
+        self.matches_from(0, &word, 0, exclude)
+    }
+
+    // Matches the tokens from `i` onwards against the word from `j` onwards
+    fn matches_from(&self, mut i: usize, word: &[char], mut j: usize, exclude: &str) -> bool {
+        loop {
+            let w = word.get(j).copied();
+            let word_break = w == Some(' ') || w == Some('-');
+            match self.tokens.get(i) {
+                Some(Token::AnyRun) => {
+                    // Try each number of characters in turn
+                    for end in j..=word.len() {
+                        if self.matches_from(i + 1, word, end, exclude) {
+                            return true;
+                        }
+                        if end < word.len() && exclude.contains(word[end]) {
+                            return false;
+                        }
+                    }
+                    return false;
+                }
+                Some(Token::WordBreak) if word_break => (i, j) = (i + 1, j + 1),
+                Some(Token::WordBreak) => return false,
+                Some(Token::Apostrophe) if w == Some('\'') => (i, j) = (i + 1, j + 1),
+                Some(Token::Apostrophe) => i += 1,
+                // Apostrophes in the word don't need to be in the pattern
+                _ if w == Some('\'') => j += 1,
+                _ if word_break => return false,
+                None => return w.is_none(),
+                Some(token) => {
+                    let Some(c) = w else {
+                        return false;
+                    };
+                    let matched = match token {
+                        Token::Letter(letter) => c == *letter,
+                        // Letters given explicitly can be matched even if excluded
+                        Token::Class {
+                            letters,
+                            negated: false,
+                        } => letters.contains(c),
+                        Token::Class {
+                            letters,
+                            negated: true,
+                        } => c.is_alphabetic() && !letters.contains(c) && !exclude.contains(c),
+                        _ => !exclude.contains(c),
+                    };
+                    if !matched {
+                        return false;
+                    }
+                    (i, j) = (i + 1, j + 1);
+                }
+            }
+        }
+    }
+}
+
+pub fn wordle(
+    search_string: &str,
+    index: &WordIndex,
+    exclude: &str,
+    include: &str,
+) -> Result<Vec<String>, LookupError> {
+    let constraints = WordleConstraints::from_search(search_string, exclude, include)?;
+    Ok(constraints.candidates(index))
+}
+
+pub fn check_yellow_letters_exist(w: &str, search_string: &str, yellow_letters: &str) -> bool {
+    // check that all "yellow" letters in the search_string exist in the word
+    // BUT not at their position in the search string
+    // we can also ignore any matches at positions which are "green"
+    // To simplify the logic we remove any "green" letters from the word first
+    // (whatever length the pattern is, as Wordle can have 4 to 11 letters)
+    let word: String = w
+        .chars()
+        .zip(search_string.chars().chain(std::iter::repeat('_')))
+        // replace with an arbitrary non alpha character
+        .map(|(c, s)| if s.is_ascii_alphabetic() { '.' } else { c })
+        .collect();
+    // Now we can just check all of the yellow letters exist
+    yellow_letters.chars().all(|c| word.contains(c))
+}
+
+/// The colour of a Wordle tile
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Tile {
+    Grey,
+    Yellow,
+    Green,
+}
+
+/// The tiles Wordle shows for a guess. A repeated letter is only yellow as
+/// many times as it's in the answer but not already green, e.g. guessing
+/// "eerie" for "tepee" gives yellow, green, grey, grey, green. The guess
+/// must be as long as the answer, with the lengths Wordle is played with.
+pub fn wordle_feedback(guess: &str, answer: &str) -> Result<Vec<Tile>, LookupError> {
+    let length = answer.len();
+    if guess.len() != length || !WORDLE_LENGTHS.contains(&length) {
+        return Err(LookupError::WrongLength {
+            expected: length.clamp(*WORDLE_LENGTHS.start(), *WORDLE_LENGTHS.end()),
+            actual: guess.len(),
+        });
+    }
+    let mut code = feedback_code(guess.as_bytes(), answer.as_bytes());
+    Ok((0..length)
+        .map(|_| {
+            let tile = [Tile::Grey, Tile::Yellow, Tile::Green][code % 3];
+            code /= 3;
+            tile
+        })
+        .collect())
+}
+
+// The feedback as a base 3 number (grey 0, yellow 1, green 2), first letter
+// lowest, so that guesses can be scored quickly
+fn feedback_code(guess: &[u8], answer: &[u8]) -> usize {
+    let mut unmatched = [0u8; 256];
+    for (g, a) in guess.iter().zip(answer) {
+        if g != a {
+            unmatched[*a as usize] += 1;
+        }
+    }
+    let mut code = 0;
+    let mut place = 1;
+    for (g, a) in guess.iter().zip(answer) {
+        if g == a {
+            code += 2 * place;
+        } else if unmatched[*g as usize] > 0 {
+            unmatched[*g as usize] -= 1;
+            code += place;
+        }
+        place *= 3;
+    }
+    code
+}
+
+/// The colours for a guess on each of several boards, with None for a board
+/// they weren't given for
+pub type BoardsFeedback = Vec<Option<Vec<Tile>>>;
+
+/// The word lengths Wordle and its variants are played with
+pub const WORDLE_LENGTHS: std::ops::RangeInclusive<usize> = 4..=11;
+
+/// What's known about a Wordle answer: the green letters, the letters that
+/// can't be in each position (yellow or grey there), and how many times
+/// each letter appears at least and (once it's gone grey) at most
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WordleConstraints {
+    pub greens: Vec<Option<char>>,
+    pub not_at: Vec<HashSet<char>>,
+    pub min_counts: HashMap<char, usize>,
+    pub max_counts: HashMap<char, usize>,
+}
+
+impl WordleConstraints {
+    /// No constraints yet on a word of the given length
+    pub fn new(length: usize) -> WordleConstraints {
+        WordleConstraints {
+            greens: vec![None; length],
+            not_at: vec![HashSet::new(); length],
+            ..Default::default()
+        }
+    }
+
+    /// The constraints from the -W search string (green letters in place) and
+    /// the -i (yellow) and -x (grey) letters. A yellow letter that's also
+    /// green means there's another one, and a grey letter that's also green
+    /// means there are no more.
+    pub fn from_search(
+        search_string: &str,
+        exclude: &str,
+        include: &str,
+    ) -> Result<WordleConstraints, LookupError> {
+        let length = search_string.len();
+        if !WORDLE_LENGTHS.contains(&length) {
+            return Err(LookupError::WrongLength {
+                expected: length.clamp(*WORDLE_LENGTHS.start(), *WORDLE_LENGTHS.end()),
+                actual: length,
+            });
+        }
+        let mut constraints = WordleConstraints::new(length);
+        for (i, c) in search_string.to_lowercase().chars().enumerate() {
+            if c.is_ascii_alphabetic() {
+                constraints.greens[i] = Some(c);
+                *constraints.min_counts.entry(c).or_default() += 1;
+            }
+        }
+        for c in include.to_lowercase().chars() {
+            *constraints.min_counts.entry(c).or_default() += 1;
+        }
+        for c in exclude.to_lowercase().chars() {
+            let greens = constraints.greens.iter().filter(|g| **g == Some(c)).count();
+            constraints.max_counts.insert(c, greens);
+        }
+        Ok(constraints)
+    }
+
+    /// Parses the feedback for a guess written like "crane:gy..b", with g for
+    /// green, y for yellow, and . (or b for black) for grey
+    pub fn parse_feedback(feedback: &str) -> Result<(String, Vec<Tile>), LookupError> {
+        let (guess, mut boards) = WordleConstraints::parse_boards_feedback(feedback)?;
+        match (boards.pop(), boards.is_empty()) {
+            (Some(Some(tiles)), true) => Ok((guess, tiles)),
+            _ => Err(LookupError::BadPattern(format!(
+                "expected the colours for one board in '{}'",
+                feedback
+            ))),
+        }
+    }
+
+    /// Parses the feedback for a guess on several boards, with the colours
+    /// for each board separated by commas, e.g. "crane:gy..b,.....,ggggg,".
+    /// A board left empty (such as one already solved) gives None.
+    pub fn parse_boards_feedback(feedback: &str) -> Result<(String, BoardsFeedback), LookupError> {
+        let bad = |why: &str| LookupError::BadPattern(format!("{} in '{}'", why, feedback));
+        let (guess, colours) = feedback
+            .split_once(':')
+            .ok_or_else(|| bad("missing ':' between the guess and its colours"))?;
+        if !guess.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(bad("the guess should be all letters"));
+        }
+        let boards = colours
+            .split(',')
+            .map(|colours| {
+                if colours.is_empty() {
+                    return Ok(None);
+                }
+                if colours.len() != guess.len() {
+                    return Err(bad("a colour is needed for each letter"));
+                }
+                colours
+                    .chars()
+                    .map(|c| match c.to_ascii_lowercase() {
+                        'g' => Ok(Tile::Green),
+                        'y' => Ok(Tile::Yellow),
+                        '.' | 'b' => Ok(Tile::Grey),
+                        _ => Err(bad(&format!("unexpected colour '{}'", c))),
+                    })
+                    .collect::<Result<Vec<Tile>, LookupError>>()
+                    .map(Some)
+            })
+            .collect::<Result<BoardsFeedback, LookupError>>()?;
+        Ok((guess.to_lowercase(), boards))
+    }
+
+    /// Adds what the feedback for a guess tells us
+    pub fn add_feedback(&mut self, guess: &str, tiles: &[Tile]) -> Result<(), LookupError> {
+        if guess.len() != self.greens.len() || tiles.len() != self.greens.len() {
+            return Err(LookupError::WrongLength {
+                expected: self.greens.len(),
+                actual: guess.len(),
+            });
+        }
+        let letters: Vec<char> = guess.to_lowercase().chars().collect();
+        for (i, (letter, tile)) in letters.iter().zip(tiles).enumerate() {
+            match tile {
+                Tile::Green => self.greens[i] = Some(*letter),
+                Tile::Yellow | Tile::Grey => {
+                    self.not_at[i].insert(*letter);
+                }
+            }
+        }
+        for letter in &letters {
+            let tiles_for_letter = || letters.iter().zip(tiles).filter(|(l, _)| *l == letter);
+            let found = tiles_for_letter()
+                .filter(|(_, tile)| **tile != Tile::Grey)
+                .count();
+            let min = self.min_counts.entry(*letter).or_default();
+            *min = found.max(*min);
+            if tiles_for_letter().any(|(_, tile)| *tile == Tile::Grey) {
+                let max = self.max_counts.entry(*letter).or_insert(found);
+                *max = found.min(*max);
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a word could be the answer
+    pub fn matches(&self, word: &str) -> bool {
+        let letters: Vec<char> = word.chars().collect();
+        if letters.len() != self.greens.len() {
+            return false;
+        }
+        for (i, letter) in letters.iter().enumerate() {
+            match self.greens[i] {
+                Some(green) if green != *letter => return false,
+                Some(_) => {}
+                None if self.not_at[i].contains(letter) => return false,
+                None => {}
+            }
+        }
+        let count = |c: &char| letters.iter().filter(|l| *l == c).count();
+        self.min_counts.iter().all(|(c, min)| count(c) >= *min)
+            && self.max_counts.iter().all(|(c, max)| count(c) <= *max)
+    }
+
+    /// Whether a guess is allowed in hard mode: the green letters must be in
+    /// place and the yellow ones used somewhere (but grey ones may be used too)
+    pub fn allows_in_hard_mode(&self, guess: &str) -> bool {
+        let letters: Vec<char> = guess.chars().collect();
+        letters.len() == self.greens.len()
+            && self
+                .greens
+                .iter()
+                .zip(&letters)
+                .all(|(green, letter)| green.is_none_or(|g| g == *letter))
+            && self
+                .min_counts
+                .iter()
+                .all(|(c, min)| letters.iter().filter(|l| *l == c).count() >= *min)
+    }
+
+    /// Whether every letter is green
+    pub fn is_solved(&self) -> bool {
+        self.greens.iter().all(Option::is_some)
+    }
+
+    /// The words in the index which could be the answer
+    pub fn candidates(&self, index: &WordIndex) -> Vec<String> {
+        index
+            .words()
+            .iter()
+            .filter(|word| self.matches(word))
+            .cloned()
+            .collect()
+    }
+}
+
+/// How good a Wordle guess is, given the possible answers
+#[derive(Debug, PartialEq)]
+pub struct GuessScore {
+    pub guess: String,
+    /// The expected information from the feedback, in bits
+    pub entropy: f64,
+    /// How many possible answers there will be on average after the guess
+    pub expected_remaining: f64,
+    /// Whether the guess could be the answer itself
+    pub possible_answer: bool,
+}
+
+/// Scores guesses by the expected information their feedback would give
+/// about the possible answers, and returns the best `count`. Guesses that
+/// could be the answer win ties. Scoring every guess against every answer
+/// takes seconds for the full word list, so only `pool` of the possible
+/// answers, and `pool` of the other guesses, are scored: those whose letters
+/// split the answers most evenly (0 scores every guess).
+pub fn suggest_guesses(
+    answers: &[String],
+    guesses: &[String],
+    count: usize,
+    pool: usize,
+) -> Vec<GuessScore> {
+    suggest_guesses_for_boards(&[answers.to_vec()], guesses, count, pool)
+}
+
+/// The `pool` for suggest_guesses() used by --suggest and --play unless
+/// --guess-pool is given
+pub const DEFAULT_GUESS_POOL: usize = 500;
+
+/// Like suggest_guesses(), for several boards played at once (as in Quordle
+/// or Octordle) with the possible answers for each unsolved board. The boards
+/// are independent, so a guess's information is the total over all of them.
+pub fn suggest_guesses_for_boards(
+    boards: &[Vec<String>],
+    guesses: &[String],
+    count: usize,
+    pool: usize,
+) -> Vec<GuessScore> {
+    let boards: Vec<&Vec<String>> = boards.iter().filter(|b| !b.is_empty()).collect();
+    if boards.is_empty() {
+        return Vec::new();
+    }
+    let possible: HashSet<&String> = boards.iter().copied().flatten().collect();
+    let length = boards[0][0].len();
+    let mut buckets = FeedbackBuckets::new(length);
+    let mut scores: Vec<GuessScore> = likely_guesses(&boards, &possible, guesses, pool)
+        .into_iter()
+        .map(|guess| {
+            let mut entropy = 0.0;
+            let mut expected_remaining = 0.0;
+            for answers in &boards {
+                let (e, remaining) = buckets.information(guess, answers);
+                entropy += e;
+                expected_remaining += remaining;
+            }
+            GuessScore {
+                guess: guess.clone(),
+                entropy,
+                expected_remaining,
+                possible_answer: possible.contains(guess),
+            }
+        })
+        .collect();
+    scores.sort_by(|a, b| {
+        b.entropy
+            .total_cmp(&a.entropy)
+            .then(b.possible_answer.cmp(&a.possible_answer))
+            .then(a.guess.cmp(&b.guess))
+    });
+    scores.truncate(count);
+    scores
+}
+
+// The guesses worth scoring: up to `pool` of the possible answers and of the
+// other guesses, those whose letters are in closest to half the answers, so
+// split them most evenly
+fn likely_guesses<'a>(
+    boards: &[&Vec<String>],
+    possible: &HashSet<&String>,
+    guesses: &'a [String],
+    pool: usize,
+) -> Vec<&'a String> {
+    let mut containing = [0usize; 256];
+    let mut total = 0;
+    for answer in boards.iter().copied().flatten() {
+        let mut seen = [false; 256];
+        for b in answer.bytes() {
+            if !seen[b as usize] {
+                seen[b as usize] = true;
+                containing[b as usize] += 1;
+            }
+        }
+        total += 1;
+    }
+    let split = |guess: &String| -> usize {
+        let mut seen = [false; 256];
+        guess
+            .bytes()
+            .filter(|b| !std::mem::replace(&mut seen[*b as usize], true))
+            .map(|b| containing[b as usize].min(total - containing[b as usize]))
+            .sum()
+    };
+    let (mut answers, mut others): (Vec<&String>, Vec<&String>) =
+        guesses.iter().partition(|guess| possible.contains(guess));
+    for list in [&mut answers, &mut others] {
+        if pool > 0 && list.len() > pool {
+            list.sort_by_cached_key(|guess| std::cmp::Reverse(split(guess)));
+            list.truncate(pool);
+        }
+    }
+    answers.extend(others);
+    answers
+}
+
+// How many answers give each feedback to a guess, indexed by feedback_code(),
+// with the codes seen so they can be cleared quickly for the next guess
+struct FeedbackBuckets {
+    counts: Vec<u32>,
+    seen: Vec<usize>,
+}
+
+impl FeedbackBuckets {
+    fn new(length: usize) -> FeedbackBuckets {
+        FeedbackBuckets {
+            counts: vec![0; 3usize.pow(length as u32)],
+            seen: Vec::new(),
+        }
+    }
+
+    // The entropy of the feedback for a guess over the possible answers, and
+    // how many answers would be left on average
+    fn information(&mut self, guess: &str, answers: &[String]) -> (f64, f64) {
+        for answer in answers {
+            let code = feedback_code(guess.as_bytes(), answer.as_bytes());
+            if self.counts[code] == 0 {
+                self.seen.push(code);
+            }
+            self.counts[code] += 1;
+        }
+        let total = answers.len() as f64;
+        let mut entropy = 0.0;
+        let mut expected_remaining = 0.0;
+        for code in self.seen.drain(..) {
+            let size = std::mem::take(&mut self.counts[code]) as f64;
+            let p = size / total;
+            entropy -= p * p.log2();
+            expected_remaining += p * size;
+        }
+        (entropy, expected_remaining)
+    }
+}
+
+pub fn expand_numbers(search_string: &str) -> String {
+    let mut res = "".to_string();
+    let mut num = 0;
+    for c in search_string.chars() {
+        if c.is_numeric() {
+            if num != 0 {
+                num *= 10;
+            }
+            num += c.to_digit(10).unwrap();
+        } else {
+            if num != 0 {
+                for _ in 0..num {
+                    res.push('.');
+                }
+            }
+            if c == ' ' {
+                res.push('/');
+            } else {
+                res.push(c);
+            }
+            num = 0;
+        }
+    }
+    if num != 0 {
+        for _ in 0..num {
+            res.push('.');
+        }
+    }
+    res
+}
+pub fn regex_lookup(search_string: &str, index: &WordIndex) -> Result<Vec<String>, LookupError> {
+    let mut results: Vec<String> = Vec::new();
+    let re = Regex::new(search_string).map_err(|e| LookupError::BadPattern(e.to_string()))?;
+
+    for word in index.words() {
+        if re.is_match(word) {
+            results.push(word.to_string());
+        }
+    }
+    Ok(results)
+}
+
+/// Looks up words by their pattern of repeated letters, e.g. "1231" or
+/// "ABCA" matches "that" but not "thus", and "1_1_" ('_' or '.' being any
+/// letter) matches "pops". Digits and capitals are variables: the same
+/// variable is always the same letter and different variables are different
+/// letters (and not any lower case letter given in the pattern, as in a codeword).
+pub fn variable_lookup(pattern: &str, index: &WordIndex) -> Result<Vec<String>, LookupError> {
+    let pattern: Vec<char> = pattern.chars().collect();
+    for c in &pattern {
+        if !(c.is_ascii_alphanumeric() || *c == '_' || *c == '.' || is_separator(*c)) {
+            return Err(LookupError::BadPattern(format!(
+                "unexpected '{}' in letter pattern",
+                c
+            )));
+        }
+    }
+    let length = pattern.iter().filter(|c| !is_separator(**c)).count();
+    Ok(index
+        .with_length(length)
+        .filter(|word| matches_variables(word, &pattern))
+        .map(|word| word.to_string())
+        .collect())
+}
+
+fn matches_variables(word: &str, pattern: &[char]) -> bool {
+    let word: Vec<char> = word.chars().filter(|c| *c != '\'').collect();
+    let pattern: Vec<char> = pattern.iter().copied().filter(|c| *c != '\'').collect();
+    if word.len() != pattern.len() {
+        return false;
+    }
+    let literals: Vec<char> = pattern
+        .iter()
+        .copied()
+        .filter(|c| c.is_ascii_lowercase())
+        .collect();
+    let mut letters: HashMap<char, char> = HashMap::new();
+    for (w, p) in word.iter().zip(&pattern) {
+        let word_break = *w == ' ' || *w == '-';
+        if is_separator(*p) || word_break {
+            if !(is_separator(*p) && word_break) {
+                return false;
+            }
+        } else if p.is_ascii_digit() || p.is_ascii_uppercase() {
+            match letters.get(p) {
+                Some(letter) if letter != w => return false,
+                Some(_) => {}
+                None => {
+                    if literals.contains(w) || letters.values().any(|letter| letter == w) {
+                        return false;
+                    }
+                    letters.insert(*p, *w);
+                }
+            }
+        } else if *p != '_' && *p != '.' && p != w {
+            return false;
+        }
+    }
+    true
+}
+
+/// The pattern of repeated letters in a word, with each new letter given the
+/// next capital, e.g. "that" gives "ABCA". Spaces are kept.
+pub fn letter_pattern(word: &str) -> String {
+    let letters: Vec<char> = word.chars().filter(|c| !c.is_whitespace()).collect();
+    let mut pattern = repeat_pattern(&letters).into_iter();
+    word.chars()
+        .map(|c| match c.is_whitespace() {
+            true => ' ',
+            false => (b'A' + pattern.next().unwrap_or(0)) as char,
+        })
+        .collect()
+}
+
+// Numbers each distinct item in order of first appearance, e.g. [7, 3, 7] gives [0, 1, 0]
+fn repeat_pattern<T: PartialEq>(items: &[T]) -> Vec<u8> {
+    let mut seen: Vec<&T> = Vec::new();
+    items
+        .iter()
+        .map(|item| match seen.iter().position(|s| *s == item) {
+            Some(pos) => pos as u8,
+            None => {
+                seen.push(item);
+                (seen.len() - 1) as u8
+            }
+        })
+        .collect()
+}
+
+/// A codeword puzzle: a crossword grid where each letter has been replaced
+/// by a number from 1 to 26
+#[derive(Debug, PartialEq)]
+pub struct Codeword {
+    /// The grid's rows, with None for a black square
+    pub cells: Vec<Vec<Option<usize>>>,
+    /// Letters given at the start, by number
+    pub given: HashMap<usize, char>,
+}
+
+impl Codeword {
+    /// Reads a grid written as rows of numbers separated by spaces or commas,
+    /// with '#' or '.' for a black square. Given letters can follow on lines
+    /// like "3=a 17=q".
+    pub fn parse(text: &str) -> Result<Codeword, LookupError> {
+        let mut cells: Vec<Vec<Option<usize>>> = Vec::new();
+        let mut given: HashMap<usize, char> = HashMap::new();
+        for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            if line.contains('=') {
+                given.extend(Codeword::parse_given(line)?);
+                continue;
+            }
+            let mut row: Vec<Option<usize>> = Vec::new();
+            for cell in line.split([' ', ',', '\t']).filter(|cell| !cell.is_empty()) {
+                row.push(match cell {
+                    "#" | "." => None,
+                    number => Some(codeword_number(number)?),
+                });
+            }
+            cells.push(row);
+        }
+        if cells.is_empty() {
+            return Err(LookupError::BadPattern("empty codeword grid".to_string()));
         }
-        // We now just have to ensure the word contains the mandatory letter
-        // which should be the first letter of the search string
-        let c = search_string.chars().next().unwrap();
-        if !word.contains(c) {
-            continue;
+        // Short rows are taken to end in black squares
+        let width = cells.iter().map(|row| row.len()).max().unwrap_or(0);
+        for row in cells.iter_mut() {
+            row.resize(width, None);
         }
-        // If we get here, we haven't failed any checks, so it's a match
-        if debug {
-            println!(" *** match ***");
-        } else {
-            results.push(word.to_string());
+        Ok(Codeword { cells, given })
+    }
+
+    /// Parses given letters like "3=a,17=q"
+    pub fn parse_given(key: &str) -> Result<HashMap<usize, char>, LookupError> {
+        parse_key(key)?
+            .into_iter()
+            .map(|(number, letter)| Ok((codeword_number(&number)?, letter)))
+            .collect()
+    }
+
+    /// The numbers making up each word in the grid, across then down
+    pub fn words(&self) -> Vec<Vec<usize>> {
+        let mut words: Vec<Vec<usize>> = Vec::new();
+        let height = self.cells.len();
+        let width = self.cells[0].len();
+        let mut add_runs = |cells: Vec<Option<usize>>| {
+            for run in cells.split(|cell| cell.is_none()) {
+                if run.len() > 1 {
+                    words.push(run.iter().flatten().copied().collect());
+                }
+            }
+        };
+        for row in &self.cells {
+            add_runs(row.clone());
         }
+        for col in 0..width {
+            add_runs((0..height).map(|row| self.cells[row][col]).collect());
+        }
+        words
     }
-    results
 }
 
-pub fn panagram(
-    search_string: &str,
-    word_list: &[String],
-    anagrams: &HashMap<String, Vec<usize>>,
-) -> Vec<String> {
-    let mut results: Vec<String> = Vec::new();
-    if search_string.len() != 9 {
-        println!("Error: search string must have nine letters");
-        exit(3);
-    }
-    let mut chars: Vec<_> = search_string.chars().collect();
-    let required_letter = chars[0];
-    chars.remove(0);
-    let mut lookups = HashSet::new();
-    for i in 3..9 {
-        for word in chars.iter().permutations(i).unique() {
-            let w1: String = required_letter.to_string();
-            let w2: String = word.into_iter().collect();
-            let word_str: String = sort_word(&format!("{}{}", w1, w2));
-            lookups.insert(word_str);
+fn codeword_number(number: &str) -> Result<usize, LookupError> {
+    number
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .filter(|n| (1..=26).contains(n))
+        .ok_or(LookupError::BadPattern(format!(
+            "'{}' isn't a codeword number (1-26)",
+            number
+        )))
+}
+
+/// A cryptogram: text where each letter has been swapped for another
+#[derive(Debug, PartialEq)]
+pub struct Cryptogram {
+    pub text: String,
+}
+
+impl Cryptogram {
+    pub fn new(text: &str) -> Cryptogram {
+        Cryptogram {
+            text: text.to_string(),
         }
     }
-    for word in lookups {
-        if let Some(indices) = anagrams.get(&word) {
-            for idx in indices {
-                results.push(word_list[*idx].to_string());
+
+    /// The words of the text (split at spaces and hyphens), as letters 0-25,
+    /// ignoring any punctuation
+    pub fn words(&self) -> Vec<Vec<usize>> {
+        self.text
+            .split(|c: char| c.is_whitespace() || c == '-')
+            .map(|word| {
+                word.chars()
+                    .filter(|c| c.is_ascii_alphabetic())
+                    .map(|c| (c.to_ascii_uppercase() as u8 - b'A') as usize)
+                    .collect::<Vec<usize>>()
+            })
+            .filter(|word| !word.is_empty())
+            .collect()
+    }
+
+    /// Parses known letters like "G=t,H=e"
+    pub fn parse_given(key: &str) -> Result<HashMap<usize, char>, LookupError> {
+        parse_key(key)?
+            .into_iter()
+            .map(|(symbol, letter)| {
+                let mut chars = symbol.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) if c.is_ascii_alphabetic() => {
+                        Ok(((c.to_ascii_uppercase() as u8 - b'A') as usize, letter))
+                    }
+                    _ => Err(LookupError::BadPattern(format!(
+                        "'{}' isn't a cryptogram letter",
+                        symbol
+                    ))),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Parses known letters for a codeword or cryptogram, e.g. "3=a,17=q" or
+/// "G=t H=e", into (symbol, letter) pairs
+pub fn parse_key(key: &str) -> Result<Vec<(String, char)>, LookupError> {
+    key.split([',', ' '])
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let bad = || LookupError::BadPattern(format!("'{}' should be like 3=a", pair));
+            let (symbol, letter) = pair.split_once('=').ok_or_else(bad)?;
+            let mut letters = letter.chars();
+            match (letters.next(), letters.next()) {
+                (Some(letter), None) if letter.is_ascii_alphabetic() && !symbol.is_empty() => {
+                    Ok((symbol.to_string(), letter.to_ascii_lowercase()))
+                }
+                _ => Err(bad()),
             }
+        })
+        .collect()
+}
+
+/// How many phrases each word appears in, as a rough measure of how common
+/// it is, e.g. "the" is in thousands and "aver" in none
+pub fn word_uses(phrases: &WordIndex) -> HashMap<String, usize> {
+    let mut uses: HashMap<String, usize> = HashMap::new();
+    for phrase in phrases.words() {
+        for word in phrase.split(|c: char| c.is_whitespace() || c == '-') {
+            *uses.entry(word.to_ascii_lowercase()).or_default() += 1;
         }
     }
-    results
+    uses
 }
 
-pub fn anagram_search(
-    search_string: &str,
-    word_list: &[String],
-    anagrams: &HashMap<String, Vec<usize>>,
-) -> Vec<String> {
-    let mut results: Vec<String> = Vec::new();
-    let search_string = sort_word(search_string);
-    if let Some(indices) = anagrams.get(&search_string) {
-        for idx in indices {
-            results.push(word_list[*idx].to_string());
+/// Solves a substitution puzzle (a codeword or cryptogram), where each word
+/// is a list of symbols and each symbol stands for a different letter, so
+/// that every word is in the index. Starting from the `given` letters, the
+/// letters each symbol could still be are narrowed down from the words which
+/// fit, then the word with the fewest possible answers is filled in, trying
+/// the commonest words (by `uses`, then the order of the index) first.
+/// Returns the key to the answer with the commonest words found, or None if
+/// there's no solution.
+pub fn solve_substitution(
+    words: &[Vec<usize>],
+    given: &HashMap<usize, char>,
+    index: &WordIndex,
+    uses: &HashMap<String, usize>,
+) -> Option<HashMap<usize, char>> {
+    let mut solver = Substitution::new(words, given, index, uses)?;
+    solver.solve_all()
+}
+
+/// The best answer found for a substitution puzzle: the key, and the words
+/// (by position) which had to be left unsolved
+#[derive(Debug, PartialEq)]
+pub struct PartialSolution {
+    pub key: HashMap<usize, char>,
+    pub unsolved: Vec<usize>,
+}
+
+/// Like solve_substitution(), but if there's no full solution (say the puzzle
+/// has a name that isn't in the index) the word which most often ran out of
+/// possible answers is left unsolved, then the next, until the others can be.
+pub fn partial_substitution(
+    words: &[Vec<usize>],
+    given: &HashMap<usize, char>,
+    index: &WordIndex,
+    uses: &HashMap<String, usize>,
+) -> Option<PartialSolution> {
+    let mut solver = Substitution::new(words, given, index, uses)?;
+    loop {
+        if let Some(key) = solver.solve_all() {
+            let unsolved = (0..words.len()).filter(|w| solver.skipped[*w]).collect();
+            return Some(PartialSolution { key, unsolved });
+        }
+        if solver.steps > MAX_SUBSTITUTION_STEPS {
+            return None;
         }
+        let worst = (0..words.len())
+            .filter(|w| !solver.skipped[*w])
+            .max_by_key(|w| solver.dead_ends[*w])?;
+        if solver.dead_ends[worst] == 0 {
+            return None;
+        }
+        solver.skipped[worst] = true;
+        solver.dead_ends.fill(0);
     }
-    results
 }
 
-pub fn lookup(search_string: &str, word_list: &[String], exclude: &str) -> Vec<String> {
-    let mut results: HashSet<String> = HashSet::new();
-    for word in word_list {
-        let mut matched = true;
-        if search_string.contains('%') {
-            let wildcard_pos = search_string.find('%').unwrap();
-            if word.len() < wildcard_pos {
+// Search state for solve_substitution()
+struct Substitution<'a> {
+    words: &'a [Vec<usize>],
+    // The dictionary words which fit each word's pattern of repeated symbols,
+    // commonest first
+    candidates: Vec<Vec<Candidate>>,
+    // The letters each symbol could be to start with (a bit for each letter)
+    given: Vec<u32>,
+    // Words being left unsolved, and how often each had no answers left,
+    // which tells us which to leave out next
+    skipped: Vec<bool>,
+    dead_ends: Vec<usize>,
+    // The symbols given, or in the words being solved
+    known: Vec<usize>,
+    // The words each symbol is in
+    containing: Vec<Vec<usize>>,
+    // Places in the search tried so far (for all the words left out), so we
+    // can give up on hopeless puzzles
+    steps: usize,
+    // Whether the allowance left out any of the search this time
+    narrowed: bool,
+    // The answer with the commonest words found so far, and its score
+    best: Option<(f64, SubstitutionState)>,
+}
+
+// A dictionary word which could be the answer to one of the puzzle's words
+#[derive(Clone)]
+struct Candidate {
+    letters: Vec<u8>,
+    // How common the word is, as the logarithm of its uses, so adding these
+    // up is like multiplying the chances of each word
+    score: f64,
+}
+
+// Where the search has got to: the letters each symbol could still be, the
+// candidates (by position) still possible for each word, and the words whose
+// candidates need checking again since their symbols' letters changed. The
+// lists of candidates are shared with the state this was copied from until
+// they change.
+#[derive(Clone)]
+struct SubstitutionState {
+    letters: Vec<u32>,
+    fitting: Vec<Rc<Vec<usize>>>,
+    stale: Vec<bool>,
+}
+
+// Enough for any proper puzzle, but stops us trying every word in the list
+// for puzzles with no solution
+const MAX_SUBSTITUTION_STEPS: usize = 100_000;
+
+const ANY_LETTER: u32 = (1 << 26) - 1;
+
+impl<'a> Substitution<'a> {
+    fn new(
+        words: &'a [Vec<usize>],
+        given: &HashMap<usize, char>,
+        index: &WordIndex,
+        uses: &HashMap<String, usize>,
+    ) -> Option<Substitution<'a>> {
+        let mut by_pattern: HashMap<(usize, Vec<u8>), Vec<Candidate>> = HashMap::new();
+        for word in words {
+            by_pattern
+                .entry((word.len(), repeat_pattern(word)))
+                .or_default();
+        }
+        let mut seen: HashSet<Vec<u8>> = HashSet::new();
+        for entry in index.words() {
+            // Apostrophes aren't written in codewords, or coded in cryptograms
+            let entry = entry.replace('\'', "");
+            if !entry.chars().all(|c| c.is_ascii_alphabetic()) {
                 continue;
             }
-        } else if word.len() != search_string.len() {
-            continue;
-        }
-        for i in 0..word.len() {
-            let c = word.as_bytes()[i] as char;
-            let mut search_char = search_string.as_bytes()[i] as char;
-            if search_char == '/' {
-                search_char = ' ';
+            let entry = entry.to_ascii_lowercase();
+            let letters: Vec<u8> = entry.bytes().map(|b| b - b'a').collect();
+            if let Some(list) = by_pattern.get_mut(&(letters.len(), repeat_pattern(&letters))) {
+                if seen.insert(letters.clone()) {
+                    let score = (1.0 + uses.get(&entry).copied().unwrap_or(0) as f64).ln();
+                    list.push(Candidate { letters, score });
+                }
             }
-            // Only exclude characters if they aren't explicitly at this position in the
-            // search string, meaning "a___t -x a" would still match "avast", for example
-            if c != search_char && exclude.contains(c) {
-                matched = false;
-                break;
+        }
+        // The sort is stable, so words used as often are left in index order,
+        // e.g. with the everyday list first
+        for list in by_pattern.values_mut() {
+            list.sort_by(|a, b| b.score.total_cmp(&a.score));
+        }
+        let candidates = words
+            .iter()
+            .map(|word| by_pattern[&(word.len(), repeat_pattern(word))].clone())
+            .collect();
+        let size = words
+            .iter()
+            .flatten()
+            .chain(given.keys())
+            .max()
+            .map_or(0, |max| max + 1);
+        let mut letters = vec![ANY_LETTER; size];
+        let mut used = 0;
+        for (symbol, letter) in given {
+            let letter = (letter.to_ascii_lowercase() as u8).wrapping_sub(b'a');
+            if letter >= 26 || used & (1 << letter) != 0 {
+                return None;
             }
-            if search_char == '_' || search_char == '.' {
-                // wildcard - we only pass this if the character we're comparing
-                // is not a space (i.e. we wouldn't want "__ _____" to match "AA AA AA")
-                if word.as_bytes()[i] == b' ' {
-                    matched = false;
-                    break;
+            letters[*symbol] = 1 << letter;
+            used |= 1 << letter;
+        }
+        let mut containing = vec![Vec::new(); size];
+        for (word, symbols) in words.iter().enumerate() {
+            for symbol in symbols {
+                if containing[*symbol].last() != Some(&word) {
+                    containing[*symbol].push(word);
                 }
-                continue;
             }
-            if search_char == '%' {
-                // match any word past this point
-                break;
+        }
+        Some(Substitution {
+            words,
+            candidates,
+            given: letters,
+            skipped: vec![false; words.len()],
+            dead_ends: vec![0; words.len()],
+            known: Vec::new(),
+            containing,
+            steps: 0,
+            narrowed: false,
+            best: None,
+        })
+    }
+
+    // Solves all the words not being skipped, returning the key to the
+    // answer with the commonest words found
+    fn solve_all(&mut self) -> Option<HashMap<usize, char>> {
+        let mut known: Vec<usize> = (0..self.words.len())
+            .filter(|w| !self.skipped[*w])
+            .flat_map(|w| self.words[w].iter().copied())
+            .chain((0..self.given.len()).filter(|symbol| self.given[*symbol] != ANY_LETTER))
+            .collect();
+        known.sort();
+        known.dedup();
+        self.known = known;
+        let mut state = SubstitutionState {
+            letters: vec![ANY_LETTER; self.given.len()],
+            fitting: self
+                .candidates
+                .iter()
+                .map(|list| Rc::new((0..list.len()).collect()))
+                .collect(),
+            stale: vec![true; self.words.len()],
+        };
+        for symbol in 0..self.given.len() {
+            if !self.narrow(symbol, self.given[symbol], &mut state) {
+                return None;
             }
-            if search_char != word.as_bytes()[i] as char {
-                matched = false;
+        }
+        // Looking first at answers which mostly take the commonest words finds
+        // good ones quickly, and those rule out much of the rest of the search
+        let mut allowance = 0;
+        loop {
+            self.narrowed = false;
+            self.solve(state.clone(), allowance);
+            if !self.narrowed || self.steps > MAX_SUBSTITUTION_STEPS {
                 break;
             }
+            allowance = allowance * 2 + 1;
         }
-        if matched {
-            results.insert(word.to_string());
-        }
+        let (_, solved) = self.best.take()?;
+        Some(
+            self.known
+                .iter()
+                .map(|symbol| (*symbol, solved.letters[*symbol]))
+                .filter(|(_, letters)| letters.count_ones() == 1)
+                .map(|(symbol, letters)| (symbol, (b'a' + letters.trailing_zeros() as u8) as char))
+                .collect(),
+        )
     }
-    results.into_iter().collect()
-}
 
-pub fn wordle(
-    search_string: &str,
-    word_list: &[String],
-    exclude: &str,
-    include: &str,
-) -> Vec<String> {
-    // First we do a lookup using just the "green" letters
-    // (i.e. those supplied in the search string), excluding the exclude letters:
-    let results = lookup(search_string, word_list, exclude);
-    // Now we can go through the results and weed out items that don't have the "yellow" letters
-    let mut matches: Vec<String> = Vec::new();
-    for word in &results {
-        if check_yellow_letters_exist(word, search_string, include) {
-            matches.push(word.clone());
+    // Looks for answers from `state` on, keeping the one with the commonest
+    // words in `self.best`, with less common candidates taking up more of
+    // the `allowance` for straying from the commonest
+    fn solve(&mut self, mut state: SubstitutionState, allowance: usize) {
+        self.steps += 1;
+        if self.steps > MAX_SUBSTITUTION_STEPS || !self.propagate(&mut state) {
+            return;
         }
-    }
-    matches
-}
-
-pub fn check_yellow_letters_exist(w: &str, search_string: &str, yellow_letters: &str) -> bool {
-    // check that all "yellow" letters in the search_string exist in the word
-    // BUT not at their position in the search string
-    // we can also ignore any matches at positions which are "green"
-    // To simplify the logic we remove any "green" letters from the word first
-    let mut word = w.to_string();
-    for i in 0..5 {
-        if search_string.as_bytes()[i].is_ascii_alphabetic() {
-            word.replace_range(i..=i, "."); // replace with an arbitrary non alpha character
+        // The first candidate left for each word is the commonest, so nothing
+        // from here on can do better than all of those together
+        let score: f64 = (0..self.words.len())
+            .filter(|w| !self.skipped[*w])
+            .map(|w| self.candidates[w][state.fitting[w][0]].score)
+            .sum();
+        if self.best.as_ref().is_some_and(|(best, _)| score <= *best) {
+            return;
         }
-    }
-    // Now we can just check all of the yellow letters exist
-    for i in 0..yellow_letters.len() {
-        let c = yellow_letters.as_bytes()[i] as char;
-        if !word.contains(c) {
-            return false;
+        // The word with the fewest possible answers is the best one to try next
+        let Some(word) = (0..self.words.len())
+            .filter(|w| !self.skipped[*w] && state.fitting[*w].len() > 1)
+            .min_by_key(|w| state.fitting[*w].len())
+        else {
+            self.best = Some((score, state));
+            return;
+        };
+        let fitting = Rc::clone(&state.fitting[word]);
+        if fitting.len() > allowance + 1 {
+            self.narrowed = true;
+        }
+        for (strays, candidate) in fitting.iter().take(allowance + 1).enumerate() {
+            let mut next = state.clone();
+            next.fitting[word] = Rc::new(vec![*candidate]);
+            next.stale[word] = true;
+            self.solve(next, allowance - strays);
         }
     }
-    true
-}
 
-pub fn expand_numbers(search_string: &str) -> String {
-    let mut res = "".to_string();
-    let mut num = 0;
-    for c in search_string.chars() {
-        if c.is_numeric() {
-            if num != 0 {
-                num *= 10;
+    // Rules out whatever the choices made so far make impossible, until
+    // nothing more changes: candidates using a letter their symbol can't be,
+    // letters a symbol has in none of its words' candidates, and letters
+    // already taken by another symbol. Returns false if a word has no
+    // candidates or a symbol no letter left.
+    fn propagate(&mut self, state: &mut SubstitutionState) -> bool {
+        while let Some(word) = (0..self.words.len()).find(|w| state.stale[*w]) {
+            state.stale[word] = false;
+            if self.skipped[word] {
+                continue;
             }
-            num += c.to_digit(10).unwrap();
-        } else {
-            if num != 0 {
-                for _ in 0..num {
-                    res.push('.');
+            let symbols = &self.words[word];
+            let candidates = &self.candidates[word];
+            let letters = &state.letters;
+            let fits = |c: &usize| {
+                symbols
+                    .iter()
+                    .zip(&candidates[*c].letters)
+                    .all(|(symbol, letter)| letters[*symbol] & (1 << letter) != 0)
+            };
+            let fitting = &mut state.fitting[word];
+            if !fitting.iter().all(fits) {
+                Rc::make_mut(fitting).retain(fits);
+            }
+            if fitting.is_empty() {
+                self.dead_ends[word] += 1;
+                return false;
+            }
+            let mut possible = vec![0u32; symbols.len()];
+            for c in fitting.iter() {
+                for (pos, letter) in candidates[*c].letters.iter().enumerate() {
+                    possible[pos] |= 1 << letter;
                 }
             }
-            if c == ' ' {
-                res.push('/');
-            } else {
-                res.push(c);
+            for (symbol, possible) in symbols.iter().zip(possible) {
+                if !self.narrow(*symbol, state.letters[*symbol] & possible, state) {
+                    return false;
+                }
+            }
+        }
+        // The symbols can't all be different letters if there's no way of
+        // pairing them up with one each
+        let mut symbol_for: [Option<usize>; 26] = [None; 26];
+        for symbol in &self.known {
+            if !self.pair_up(*symbol, state, &mut symbol_for, &mut 0) {
+                return false;
             }
-            num = 0;
         }
+        true
     }
-    if num != 0 {
-        for _ in 0..num {
-            res.push('.');
+
+    // Narrows down the letters `symbol` could be, marking its words to be
+    // checked again, and if that leaves one letter, rules it out for every
+    // other symbol. Returns false if a symbol has no letter left.
+    fn narrow(&self, symbol: usize, letters: u32, state: &mut SubstitutionState) -> bool {
+        if letters == state.letters[symbol] {
+            return true;
+        }
+        if letters == 0 {
+            return false;
+        }
+        state.letters[symbol] = letters;
+        for word in &self.containing[symbol] {
+            state.stale[*word] = true;
+        }
+        if letters.count_ones() == 1 {
+            for other in &self.known {
+                let others = state.letters[*other];
+                if *other != symbol
+                    && others & letters != 0
+                    && !self.narrow(*other, others & !letters, state)
+                {
+                    return false;
+                }
+            }
         }
+        true
     }
-    res
-}
-pub fn regex_lookup(search_string: &str, word_list: &[String]) -> Vec<String> {
-    use regex::Regex;
-    let mut results: Vec<String> = Vec::new();
-    let re = Regex::new(search_string).unwrap();
 
-    for word in word_list {
-        if re.is_match(word) {
-            results.push(word.to_string());
+    // Finds a letter for `symbol`, moving any other symbol using the one it
+    // wants on to another of its letters if need be (`tried` stops us going
+    // round in circles)
+    fn pair_up(
+        &self,
+        symbol: usize,
+        state: &SubstitutionState,
+        symbol_for: &mut [Option<usize>; 26],
+        tried: &mut u32,
+    ) -> bool {
+        for letter in 0..26 {
+            let bit = 1 << letter;
+            if state.letters[symbol] & bit == 0 || *tried & bit != 0 {
+                continue;
+            }
+            *tried |= bit;
+            let free = match symbol_for[letter] {
+                None => true,
+                Some(other) => self.pair_up(other, state, symbol_for, tried),
+            };
+            if free {
+                symbol_for[letter] = Some(symbol);
+                return true;
+            }
         }
+        false
     }
-    results
 }
 
-pub fn jumble(full_input: &str, found_letters: &str, size: u8, output_type: OutputType) {
+pub fn jumble(
+    full_input: &str,
+    found_letters: &str,
+    size: u8,
+    output_type: OutputType,
+) -> Result<(), LookupError> {
     if size > 0 && size as usize != full_input.len() {
-        println!(
-            "Error: the number of supplied letters ({}) did not match the 'size' argument",
-            full_input.len()
-        );
-        return;
+        return Err(LookupError::WrongLength {
+            expected: size as usize,
+            actual: full_input.len(),
+        });
     }
     let mut input: String = full_input.to_string();
     for c in found_letters.chars() {
@@ -292,12 +2319,7 @@ pub fn jumble(full_input: &str, found_letters: &str, size: u8, output_type: Outp
             if let Some(pos) = input.find(c) {
                 input.remove(pos);
             } else {
-                println!(
-                    "Error: You supplied a letter ({}) in the found (-f) option",
-                    c
-                );
-                println!("which does not appear in the source set of letters");
-                return;
+                return Err(LookupError::LetterNotInSource(c));
             }
         }
     }
@@ -314,6 +2336,7 @@ pub fn jumble(full_input: &str, found_letters: &str, size: u8, output_type: Outp
     chars.shuffle(&mut rng);
 
     ui::display::anagram_helper(found_letters, chars, len, output_type);
+    Ok(())
 }
 
 pub fn reverse(search_string: &str) -> Vec<String> {
@@ -325,6 +2348,478 @@ pub fn reverse(search_string: &str) -> Vec<String> {
     results
 }
 
+/// A dictionary word found hidden in some clue fodder
+#[derive(Debug, PartialEq)]
+pub struct HiddenWord {
+    pub word: String,
+    /// Where the word's letters are in the fodder (counting letters only),
+    /// as the index of the first letter and one past the last
+    pub start: usize,
+    pub end: usize,
+    /// The word is made of every `step`th letter, so 1 for a word hidden
+    /// as a whole and 2 for alternate letters
+    pub step: usize,
+    /// True if the word reads backwards in the fodder
+    pub reversed: bool,
+}
+
+impl HiddenWord {
+    /// Positions of the word's letters in the fodder (counting letters only)
+    pub fn positions(&self) -> impl Iterator<Item = usize> {
+        (self.start..self.end).step_by(self.step)
+    }
+
+    /// True if the word runs across a break between the words of the fodder,
+    /// as a setter would hide it, rather than sitting inside a single word
+    pub fn spans_words(&self, fodder: &str) -> bool {
+        let mut first = 0;
+        for word in fodder.split(|c: char| c.is_whitespace() || c == '-') {
+            let last = first + word.chars().filter(char::is_ascii_alphabetic).count();
+            if self.start >= first && self.end <= last {
+                return false;
+            }
+            first = last;
+        }
+        true
+    }
+}
+
+/// Finds every word or phrase of at least `min_len` letters hidden in the
+/// fodder, reading either forwards or backwards, e.g. "birthaim" hides "thai"
+/// and "beer iffy" hides "fire" reversed
+pub fn hidden_words(fodder: &str, index: &WordIndex, min_len: usize) -> Vec<HiddenWord> {
+    words_in_fodder(fodder, index, min_len, 1..=1)
+}
+
+/// Finds every word or phrase of at least `min_len` letters made from regular
+/// letters of the fodder (every second letter, every third...) starting
+/// anywhere and reading either forwards or backwards, e.g. "bro sneers"
+/// gives "bones"
+pub fn regular_words(fodder: &str, index: &WordIndex, min_len: usize) -> Vec<HiddenWord> {
+    let len = fodder.chars().filter(|c| c.is_ascii_alphabetic()).count();
+    // No point in steps so big that no word can be long enough
+    let max_step = len.saturating_sub(1) / min_len.saturating_sub(1).max(1);
+    words_in_fodder(fodder, index, min_len, 2..=max_step)
+}
+
+fn words_in_fodder(
+    fodder: &str,
+    index: &WordIndex,
+    min_len: usize,
+    steps: std::ops::RangeInclusive<usize>,
+) -> Vec<HiddenWord> {
+    let letters: Vec<char> = fodder
+        .chars()
+        .filter(|c| c.is_ascii_alphabetic())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    let mut results: Vec<HiddenWord> = Vec::new();
+    for step in steps {
+        for start in 0..letters.len() {
+            let mut forwards = String::new();
+            for pos in (start..letters.len()).step_by(step) {
+                forwards.push(letters[pos]);
+                if forwards.len() < min_len {
+                    continue;
+                }
+                let backwards: String = forwards.chars().rev().collect();
+                let mut candidates = vec![(&forwards, false)];
+                if backwards != forwards {
+                    candidates.push((&backwards, true));
+                }
+                for (candidate, reversed) in candidates {
+                    for word in index.find(candidate) {
+                        let hidden = HiddenWord {
+                            word: word.to_string(),
+                            start,
+                            end: pos + 1,
+                            step,
+                            reversed,
+                        };
+                        // Skip any entries duplicated in the word lists
+                        if !results.contains(&hidden) {
+                            results.push(hidden);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    results
+}
+
+/// A candidate answer built from parts of a clue, with how it was built
+#[derive(Debug, PartialEq)]
+pub struct Wordplay {
+    pub answer: String,
+    pub explanation: String,
+}
+
+/// Container clues: puts each of the `first` candidates inside each of the
+/// `second` candidates and vice versa, returning the answers in the index,
+/// e.g. "in" in "pat" gives "paint", explained as "in in pat: pa(in)t"
+pub fn containers(first: &[String], second: &[String], index: &WordIndex) -> Vec<Wordplay> {
+    let mut results: Vec<Wordplay> = Vec::new();
+    for a in first {
+        for b in second {
+            add_containers(a, b, index, &mut results);
+            add_containers(b, a, index, &mut results);
+        }
+    }
+    results
+}
+
+// Adds the answers made by putting `inner` inside `outer`
+fn add_containers(inner: &str, outer: &str, index: &WordIndex, results: &mut Vec<Wordplay>) {
+    let (inner_letters, outer_letters) = (letters_only(inner), letters_only(outer));
+    if inner_letters.is_empty() {
+        return;
+    }
+    // Strictly inside, putting one at the start or end of the other is a charade
+    for pos in 1..outer_letters.len() {
+        let (before, after) = outer_letters.split_at(pos);
+        let candidate = format!("{}{}{}", before, inner_letters, after);
+        for word in index.find(&candidate) {
+            let wordplay = Wordplay {
+                answer: word.to_string(),
+                explanation: format!(
+                    "{} in {}: {}({}){}",
+                    inner, outer, before, inner_letters, after
+                ),
+            };
+            if !results.contains(&wordplay) {
+                results.push(wordplay);
+            }
+        }
+    }
+}
+
+/// Charade clues: joins one candidate for each part in turn, returning the
+/// answers in the index. The first candidate of each part is taken to be the
+/// clue word itself, used to explain the others, e.g. parts ["river", "r"]
+/// and ["ace"] give "race", explained as "r (river) + ace"
+pub fn charades(parts: &[Vec<String>], index: &WordIndex) -> Vec<Wordplay> {
+    // Sorted, so we can quickly give up on joins which can't start any answer
+    let mut prefixes: Vec<String> = index.words().iter().map(|w| letters_only(w)).collect();
+    prefixes.sort();
+    let starts_answer = |joined: &str| {
+        let pos = prefixes.partition_point(|w| w.as_str() < joined);
+        pos < prefixes.len() && prefixes[pos].starts_with(joined)
+    };
+
+    let mut results: Vec<Wordplay> = Vec::new();
+    add_charades(parts, &mut Vec::new(), index, &starts_answer, &mut results);
+    results
+}
+
+// Adds the answers made by adding each candidate for the next part onto
+// those `chosen` for the parts before it
+fn add_charades<'a>(
+    parts: &'a [Vec<String>],
+    chosen: &mut Vec<&'a str>,
+    index: &WordIndex,
+    starts_answer: &dyn Fn(&str) -> bool,
+    results: &mut Vec<Wordplay>,
+) {
+    let joined: String = chosen
+        .iter()
+        .flat_map(|c| c.chars())
+        .filter(|c| c.is_ascii_alphabetic())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    if chosen.len() == parts.len() {
+        let explanation = chosen
+            .iter()
+            .zip(parts)
+            .map(|(c, part)| match *c == part[0] {
+                true => c.to_string(),
+                false => format!("{} ({})", c, part[0]),
+            })
+            .join(" + ");
+        for word in index.find(&joined) {
+            let wordplay = Wordplay {
+                answer: word.to_string(),
+                explanation: explanation.clone(),
+            };
+            if !results.contains(&wordplay) {
+                results.push(wordplay);
+            }
+        }
+        return;
+    }
+    if !joined.is_empty() && !starts_answer(&joined) {
+        return;
+    }
+    for candidate in &parts[chosen.len()] {
+        chosen.push(candidate);
+        add_charades(parts, chosen, index, starts_answer, results);
+        chosen.pop();
+    }
+}
+
+// Just the letters, lower case, e.g. "De facto" gives "defacto"
+fn letters_only(s: &str) -> String {
+    s.chars()
+        .filter(|c| c.is_ascii_alphabetic())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// Splits a cryptic clue into its words and the enumeration at the end,
+/// e.g. "Beer iffy about fire (4)" gives ["beer", "iffy", "about", "fire"] and [4]
+pub fn parse_clue(clue: &str) -> Result<(Vec<String>, Vec<usize>), LookupError> {
+    let clue = clue.trim();
+    let open = match clue.rfind('(') {
+        Some(open) if clue.ends_with(')') => open,
+        _ => {
+            return Err(LookupError::BadPattern(
+                "the clue needs an enumeration at the end, e.g. (4)".to_string(),
+            ))
+        }
+    };
+    let enumeration = parse_enumeration(&clue[open..])?;
+    let words: Vec<String> = clue[..open]
+        .split_whitespace()
+        .map(letters_only)
+        .filter(|word| !word.is_empty())
+        .collect();
+    if words.len() < 2 {
+        return Err(LookupError::BadPattern(
+            "the clue needs a definition and some wordplay".to_string(),
+        ));
+    }
+    Ok((words, enumeration))
+}
+
+/// An indicator in a clue: `words[start..end]` usually signal this `kind` of wordplay
+#[derive(Debug, PartialEq)]
+pub struct ClueIndicator {
+    pub start: usize,
+    pub end: usize,
+    pub kind: IndicatorType,
+}
+
+/// Finds the indicators (single words or phrases of up to three words) in a
+/// clue's words, given the contents of indicators.txt (see
+/// `file::load::indicator_map`)
+pub fn find_indicators(
+    words: &[String],
+    indicators: &HashMap<String, Vec<IndicatorType>>,
+) -> Vec<ClueIndicator> {
+    let mut results: Vec<ClueIndicator> = Vec::new();
+    for start in 0..words.len() {
+        for end in start + 1..=(start + 3).min(words.len()) {
+            let phrase = words[start..end].join(" ").to_ascii_lowercase();
+            for kind in indicators.get(&phrase).into_iter().flatten() {
+                results.push(ClueIndicator {
+                    start,
+                    end,
+                    kind: *kind,
+                });
+            }
+        }
+    }
+    results
+}
+
+/// Clue analyser: tries the wordplay each indicator in a clue could signal on
+/// the words next to it, returning candidate answers which fit the
+/// enumeration, best first, with the reasoning. The synonyms (from
+/// `thesaurus`) and abbreviations of a word or phrase are used for containers
+/// and reversals and to check the definition, which is taken to be at the
+/// start or end of the clue.
+/// Homophones need pronunciations, which we don't have, so aren't tried.
+pub fn analyse_clue(
+    words: &[String],
+    enumeration: &[usize],
+    indicators: &[ClueIndicator],
+    thesaurus: &KeyedEntries,
+    abbreviations: &KeyedEntries,
+    index: &WordIndex,
+) -> Vec<Wordplay> {
+    let synonyms = |word: &str| -> Vec<String> {
+        let word = word.to_ascii_lowercase();
+        [thesaurus, abbreviations]
+            .iter()
+            .flat_map(|entries| entries.get(&word).into_iter().flatten())
+            .cloned()
+            .collect()
+    };
+    let total: usize = enumeration.iter().sum();
+    let fits = |answer: &str| matches_enumeration(answer, enumeration);
+    // Candidates along with the words used by the wordplay, and how good they are
+    let mut candidates: Vec<(Wordplay, usize, usize, i32)> = Vec::new();
+    let mut add = |answer: &str, explanation: String, start: usize, end: usize, score: i32| {
+        if fits(answer) {
+            let wordplay = Wordplay {
+                answer: answer.to_string(),
+                explanation,
+            };
+            candidates.push((wordplay, start, end, score));
+        }
+    };
+
+    for indicator in indicators {
+        let shown = words[indicator.start..indicator.end].join(" ");
+        // Fodder is the words either side of the indicator, leaving at least
+        // one word at the start or end of the clue for the definition
+        let mut fodders: Vec<(usize, usize)> = Vec::new();
+        for start in 0..indicator.start {
+            fodders.push((start, indicator.start));
+        }
+        for end in indicator.end + 1..=words.len() {
+            fodders.push((indicator.end, end));
+        }
+        fodders.retain(|(start, end)| {
+            let (first, last) = (indicator.start.min(*start), indicator.end.max(*end));
+            first > 0 || last < words.len()
+        });
+        for (start, end) in fodders {
+            let fodder = words[start..end].join(" ");
+            let letters = letters_only(&fodder);
+            let (first, last) = (indicator.start.min(start), indicator.end.max(end));
+            match indicator.kind {
+                IndicatorType::Anagram if letters.len() == total => {
+                    let answers = match enumeration.len() {
+                        1 => anagram_search(&letters, index),
+                        _ => multi_anagram(&letters, index, enumeration, "", enumeration.len())
+                            .unwrap_or_default(),
+                    };
+                    for answer in answers {
+                        if answer != letters {
+                            let explanation =
+                                format!("anagram of '{}' (indicated by '{}')", fodder, shown);
+                            add(&answer, explanation, first, last, 30);
+                        }
+                    }
+                }
+                IndicatorType::Reversal => {
+                    // Either a reversed synonym, or a word reversed in the fodder
+                    let mut parts = vec![fodder.clone()];
+                    parts.extend(synonyms(&fodder));
+                    for part in parts {
+                        let reversed: String = letters_only(&part).chars().rev().collect();
+                        for answer in index.find(&reversed) {
+                            let explanation = match part == fodder {
+                                true => format!("'{}' reversed (indicated by '{}')", part, shown),
+                                false => format!(
+                                    "{} ({}) reversed (indicated by '{}')",
+                                    part, fodder, shown
+                                ),
+                            };
+                            add(answer, explanation, first, last, 25);
+                        }
+                    }
+                    for hidden in hidden_words(&fodder, index, total) {
+                        if hidden.reversed && letters.len() > total {
+                            let explanation = format!(
+                                "hidden reversed in '{}' (indicated by '{}')",
+                                fodder, shown
+                            );
+                            let score = hidden_score(&hidden, &fodder, 30);
+                            add(&hidden.word, explanation, first, last, score);
+                        }
+                    }
+                }
+                IndicatorType::Hidden if letters.len() > total => {
+                    for hidden in hidden_words(&fodder, index, total) {
+                        let how = match hidden.reversed {
+                            true => "hidden reversed",
+                            false => "hidden",
+                        };
+                        let explanation =
+                            format!("{} in '{}' (indicated by '{}')", how, fodder, shown);
+                        let score = if hidden.reversed { 20 } else { 30 };
+                        let score = hidden_score(&hidden, &fodder, score);
+                        add(&hidden.word, explanation, first, last, score);
+                    }
+                }
+                IndicatorType::Regular if letters.len() > total => {
+                    for hidden in regular_words(&fodder, index, total) {
+                        let mut explanation = format!("every {} letter", ordinal(hidden.step));
+                        if hidden.reversed {
+                            explanation += ", reversed,";
+                        }
+                        explanation += &format!(" of '{}' (indicated by '{}')", fodder, shown);
+                        add(&hidden.word, explanation, first, last, 25);
+                    }
+                }
+                _ => {}
+            }
+        }
+        // Containers put the word(s) on one side of the indicator inside the other
+        if indicator.kind == IndicatorType::Container
+            && indicator.start > 0
+            && indicator.end < words.len()
+        {
+            let (first, last) = (indicator.start - 1, indicator.end + 1);
+            if first > 0 || last < words.len() {
+                let expand = |word: &String| {
+                    let mut parts = vec![word.clone()];
+                    parts.extend(synonyms(word));
+                    parts
+                };
+                let (inner, outer) = (expand(&words[first]), expand(&words[indicator.end]));
+                for wordplay in containers(&inner, &outer, index) {
+                    let explanation =
+                        format!("{} (indicated by '{}')", wordplay.explanation, shown);
+                    add(&wordplay.answer, explanation, first, last, 20);
+                }
+            }
+        }
+    }
+
+    // A definition matching the answer makes it much more likely. Otherwise we
+    // prefer wordplay which leaves a definition of only a word or two.
+    for (wordplay, start, end, score) in candidates.iter_mut() {
+        let mut definitions: Vec<String> = Vec::new();
+        if *start > 0 {
+            definitions.push(words[..*start].join(" "));
+        }
+        if *end < words.len() {
+            definitions.push(words[*end..].join(" "));
+        }
+        for definition in &definitions {
+            if synonyms(definition).contains(&wordplay.answer) {
+                wordplay.explanation += &format!("; definition '{}'", definition);
+                *score += 50;
+            }
+        }
+        let left_over = words.len() - (*end - *start);
+        *score -= 5 * (left_over as i32 - 1).abs();
+    }
+    candidates.sort_by_key(|c| std::cmp::Reverse(c.3));
+    let mut results: Vec<Wordplay> = Vec::new();
+    for (wordplay, ..) in candidates {
+        // The best explanation of each answer is enough
+        if !results.iter().any(|r| r.answer == wordplay.answer) {
+            results.push(wordplay);
+        }
+    }
+    results
+}
+
+// Answers are hidden across the words of a clue, so one found inside a single
+// word is much less likely to be what the setter meant
+fn hidden_score(hidden: &HiddenWord, fodder: &str, score: i32) -> i32 {
+    match hidden.spans_words(fodder) {
+        true => score,
+        false => score - 15,
+    }
+}
+
+/// 2 -> "2nd", 3 -> "3rd" etc.
+pub fn ordinal(n: usize) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", n, suffix)
+}
+
 fn remove_whitespace(s: &mut String) {
     s.retain(|c| !c.is_whitespace());
 }
@@ -358,8 +2853,8 @@ pub fn remove_found_mismatches(
     results: &[String],
     found: String,
     exclude_phrases: bool,
-) -> Vec<String> {
-    let found_letters = expand_numbers(&found);
+) -> Result<Vec<String>, LookupError> {
+    let found_letters = expand_numbers(&expand_enumeration(&found));
     let mut new_results: Vec<String> = Vec::new();
     let mut regex_string = "(?i)^".to_string();
     for i in 0..found_letters.len() {
@@ -367,17 +2862,21 @@ pub fn remove_found_mismatches(
             regex_string.push('.');
         } else if found_letters.as_bytes()[i] == b'%' {
             regex_string.push_str(".*");
-            break;
-        } else if found_letters.as_bytes()[i] == b'/' {
-            regex_string.push(' ');
+        } else if found_letters.as_bytes()[i] == b'/' || found_letters.as_bytes()[i] == b'-' {
+            // Hyphenated words are in the lists as separate words
+            regex_string.push_str("[ -]");
+        } else if found_letters.as_bytes()[i] == b'\'' {
+            regex_string.push_str("'?");
+        } else if found_letters.as_bytes()[i] == b'@' {
+            regex_string.push_str("[aeiou]");
+        } else if found_letters.as_bytes()[i] == b'#' {
+            regex_string.push_str("[b-df-hj-np-tv-z]");
         } else {
             regex_string.push(found_letters.as_bytes()[i] as char);
         }
     }
-    if !regex_string.contains(".*") {
-        regex_string.push('$');
-    }
-    let re = Regex::new(&regex_string).unwrap();
+    regex_string.push('$');
+    let re = Regex::new(&regex_string).map_err(|e| LookupError::BadPattern(e.to_string()))?;
     for word in results {
         if exclude_phrases && word.contains(' ') {
             continue;
@@ -386,7 +2885,7 @@ pub fn remove_found_mismatches(
             new_results.push(word.to_string());
         }
     }
-    new_results
+    Ok(new_results)
 }
 
 pub fn remove_wrong_sized_words(results: &[String], length: u8) -> Vec<String> {
@@ -401,9 +2900,9 @@ pub fn remove_wrong_sized_words(results: &[String], length: u8) -> Vec<String> {
     new_results
 }
 
-pub fn define(word: &str, output_type: OutputType) {
+pub fn define(word: &str, output_type: OutputType) -> Result<(), LookupError> {
     let mut results = vec![];
-    file::load::definitions(&mut results, &word.to_ascii_lowercase());
+    file::load::definitions(&mut results, &word.to_ascii_lowercase())?;
     if output_type == OutputType::Json {
         let mut v: Vec<String> = Vec::new();
         for result in results {
@@ -418,6 +2917,38 @@ pub fn define(word: &str, output_type: OutputType) {
             println!(" * {}", result);
         }
     }
+    Ok(())
+}
+
+/// Lists the cryptic crossword abbreviations of a word, e.g. "sailor" gives
+/// "ab", "tar", "os" and "salt"
+pub fn abbreviate(word: &str, output_type: OutputType) -> Result<(), LookupError> {
+    let mut results = vec![];
+    file::load::abbreviations(&mut results, &word.to_ascii_lowercase())?;
+    if results.is_empty() && output_type != OutputType::Json {
+        println!("No abbreviations found.");
+    } else {
+        ui::display::show_results(&results, word, Action::Abbreviation, output_type);
+    }
+    Ok(())
+}
+
+/// Says which types of clue a word (or phrase) usually indicates, e.g.
+/// "about" can indicate an anagram, a container or a reversal
+pub fn indicator(word: &str, output_type: OutputType) -> Result<(), LookupError> {
+    let mut results = vec![];
+    file::load::indicators(&mut results, &word.to_ascii_lowercase())?;
+    if output_type == OutputType::Json {
+        let names: Vec<String> = results.iter().map(|t| t.to_string()).collect();
+        println!("{}", serde_json::to_string(&names).unwrap());
+    } else if results.is_empty() {
+        println!("Not a known indicator.");
+    } else {
+        for result in results {
+            println!(" * {} indicator", result);
+        }
+    }
+    Ok(())
 }
 
 pub fn expand_found_string(search_string: &str, found_letters: &str) -> String {
@@ -426,29 +2957,18 @@ pub fn expand_found_string(search_string: &str, found_letters: &str) -> String {
     if found.is_empty() {
         return found;
     }
-    found = expand_numbers(&found);
+    found = expand_numbers(&expand_enumeration(&found));
+    // Word breaks in either (e.g. from an enumeration) aren't letters to fill
+    let length = pattern_letters(search_string);
     if found.starts_with('%') {
         found.remove(0);
-        let f = found.replace('/', "");
-        for _ in 0..search_string.len() - f.len() {
+        for _ in 0..length.saturating_sub(pattern_letters(&found)) {
             found.insert(0, '.');
         }
     }
-    let nospace_found = found.replace('/', "");
-    if nospace_found.len() >= search_string.len() {
-        return found;
-    }
     // Add any required trailing wildcards
-    if search_string.len() > nospace_found.len() {
-        let mut wildcards_to_add = search_string.len() - found.len();
-        for c in found.chars() {
-            if c == '/' {
-                wildcards_to_add += 1;
-            }
-        }
-        for _ in 0..wildcards_to_add {
-            found.push('.');
-        }
+    for _ in pattern_letters(&found)..length {
+        found.push('.');
     }
     found
 }
@@ -5,9 +5,6 @@ use std::process::exit;
 
 use lookup::*;
 
-pub mod file;
-pub mod ui;
-
 // Note, word lists are generated from public domain word lists,
 // see http://wordlist.aspell.net/12dicts-readme/
 // Definitions are from https://github.com/wordset/wordset-dictionary
@@ -20,6 +17,12 @@ pub mod ui;
         .required(false)
         .args(&["wordle", "spellingbee", "panagram", "lookup", "jumble"]),
 ))]
+// ... and the games which can be played interactively with --play
+#[clap(group(
+    ArgGroup::new("games")
+        .required(false)
+        .args(&["wordle", "spellingbee"]),
+))]
 // Note, this magic incantation way of defining arguments for clap is called "derive"
 // (see https://docs.rs/clap/latest/clap/_derive/_tutorial/index.html)
 struct Args {
@@ -44,10 +47,112 @@ struct Args {
     #[arg(short, long, default_value = "", num_args = 1..)]
     define: Vec<String>,
 
+    /// List the cryptic crossword abbreviations of a word, e.g. "river" gives R, DEE, PO...
+    #[arg(long, default_value = "", num_args = 1..)]
+    abbrev: Vec<String>,
+
+    /// Say which types of cryptic clue a word usually indicates, e.g. "about"
+    /// can mean an anagram, a container or a reversal
+    #[arg(long, default_value = "", num_args = 1..)]
+    indicator: Vec<String>,
+
     /// Plain anagram solver
     #[arg(short = 'A', long, default_value_t = false)]
     anagram: bool,
 
+    /// Letters to take out of the search string before solving an anagram, e.g.
+    /// -A aldershotcafedemolition --minus aldershotdemolition. Works with -A, -M and --subanagram
+    #[arg(long, default_value = "")]
+    minus: String,
+
+    /// Multi-word anagram solver, finds combinations of words which use all the letters.
+    /// Use -E to give the answer's enumeration and -f for any letters already found.
+    #[arg(short = 'M', long, default_value_t = false)]
+    multiword: bool,
+
+    /// Enumeration (word lengths) of a multi-word anagram, e.g. 11,4.
+    /// Also filters --hidden, --regular-words, --container and --charade results
+    #[arg(short = 'E', long, default_value = "")]
+    enumeration: String,
+
+    /// Maximum number of words combined by a multi-word anagram with no enumeration,
+    /// or in a --letterboxed answer
+    #[arg(long, default_value_t = 3)]
+    max_words: usize,
+
+    /// Find all the words which can be made from some of the letters in the search string.
+    /// Use '?' for a blank tile, and see also --min-length, --max-length and --mandatory
+    #[arg(long, default_value_t = false)]
+    subanagram: bool,
+
+    /// Minimum word length for --subanagram
+    #[arg(long, default_value_t = 3)]
+    min_length: usize,
+
+    /// Maximum word length for --subanagram (0 = no limit)
+    #[arg(long, default_value_t = 0)]
+    max_length: usize,
+
+    /// Letters which must appear in every --subanagram result
+    #[arg(long, default_value = "")]
+    mandatory: String,
+
+    /// Find words hidden in the search string, reading forwards or backwards,
+    /// e.g. "birthaim" hides "thai". Use -z and -f to filter the results
+    #[arg(long, default_value_t = false)]
+    hidden: bool,
+
+    /// Find words made from regular letters of the search string (every 2nd, 3rd...
+    /// letter, reading forwards or backwards). Use -z, -E and -f to filter the results
+    #[arg(long, default_value_t = false)]
+    regular_words: bool,
+
+    /// Container clue solver, puts either part of the search string inside the other,
+    /// e.g. "in pat" gives "paint". Use -z, -E and -f to filter the results
+    #[arg(long, default_value_t = false)]
+    container: bool,
+
+    /// Charade clue solver, joins synonyms or abbreviations of each part of the search
+    /// string in turn, e.g. "river ace" gives "race". Use -z, -E and -f to filter the results
+    #[arg(long, default_value_t = false)]
+    charade: bool,
+
+    /// Analyse a whole cryptic clue, ending with its enumeration, e.g.
+    /// --clue "Beer iffy about fire (4)". Tries the wordplay signalled by each indicator
+    /// in the clue and lists the possible answers, best first, with the reasoning
+    #[arg(long, default_value = "")]
+    clue: String,
+
+    /// Also try thesaurus synonyms of each part of the search string (for --container)
+    #[arg(long, default_value_t = false)]
+    synonyms: bool,
+
+    /// Letter pattern lookup, e.g. -p 1231_ or -p ABCA. where the same digit (or capital)
+    /// is the same letter and different ones are different letters, as in codewords.
+    /// Can be combined with -f
+    #[arg(short, long, default_value = "")]
+    pattern: String,
+
+    /// Solve a codeword puzzle, given a file with the grid: rows of numbers separated by
+    /// spaces, with # for black squares. Use --key (or lines like "3=a" in the file)
+    /// for the letters given
+    #[arg(long)]
+    codeword: Option<std::path::PathBuf>,
+
+    /// Solve a cryptogram, e.g. --cryptogram "ZM ZKKOV Z WZB PVVKH GSV WLXGLI ZDZB".
+    /// Use --key for any letters you know
+    #[arg(long, default_value = "")]
+    cryptogram: String,
+
+    /// Solve a Letter Boxed puzzle, e.g. --letterboxed ABC/DEF/GHI/JKL, giving the
+    /// shortest chains of words which use every letter (see --max-words)
+    #[arg(long, default_value = "")]
+    letterboxed: String,
+
+    /// Known letters for --codeword or --cryptogram, e.g. "3=a,17=q" or "G=t"
+    #[arg(long, default_value = "")]
+    key: String,
+
     /// Regex lookup - best single quoted, normally you will need ^/$ at beginning/end
     #[arg(short = 'R', long, default_value_t = false)]
     regex: bool,
@@ -60,8 +165,49 @@ struct Args {
     #[arg(short = 'x', long, default_value = "", requires = "wordle")]
     exclude: String,
 
+    /// The colours Wordle gave a guess, e.g. --feedback crane:gy..b for green C, yellow R
+    /// and grey A, N and E (. or b for grey). Give one for each guess so far. For several
+    /// boards at once (Quordle, Octordle) separate each board's colours with commas,
+    /// leaving solved boards empty, e.g. crane:gy..b,.....,,..y..
+    #[arg(long, default_value = "", num_args = 1.., requires = "wordle")]
+    feedback: Vec<String>,
+
+    /// Number of letters for Wordle, from 4 to 11 (normally 5, or the length of the
+    /// search string)
+    #[arg(long, requires = "wordle")]
+    length: Option<usize>,
+
+    /// Suggest the best next Wordle guesses (10 unless a number is given), scored by how
+    /// much they would narrow down the possible answers
+    #[arg(long, num_args = 0..=1, default_missing_value = "10", requires = "wordle")]
+    suggest: Option<usize>,
+
+    /// How many of the possible answers, and of the other guesses, --suggest and --play
+    /// score, picking those whose letters split the answers most evenly (0 = score every
+    /// guess, which is slower)
+    #[arg(long, default_value_t = DEFAULT_GUESS_POOL, requires = "wordle")]
+    guess_pool: usize,
+
+    /// Hard mode for --suggest and --play: only suggest guesses which use the green and
+    /// yellow letters
+    #[arg(long, default_value_t = false, requires = "wordle")]
+    hard: bool,
+
+    /// Play interactively. For Wordle enter each guess and colour in its tiles, and the
+    /// possible answers and best next guesses are shown after each one. For Spelling Bee
+    /// enter the words you find, and your score and rank are kept
+    #[arg(long, default_value_t = false, requires = "games")]
+    play: bool,
+
+    /// Spelling Bee hints instead of the answers: the points needed for each rank, and
+    /// how many words start with each letter (by length) and each pair of letters
+    #[arg(long, default_value_t = false, requires = "spellingbee")]
+    hints: bool,
+
     /// Lookup partial match, e.g. "c_mp_t_r" would yield "computer". You can also look up
-    /// phrases, for example "l_k_ m_g_c" would match "like magic".
+    /// phrases, for example "l_k_ m_g_c" would match "like magic". Use % for any number of
+    /// letters, [aeiou] for one of a set of letters, [^s] for any letter but s, and @ or #
+    /// for any vowel or consonant.
     #[arg(short = 'L', long, default_value_t = false)]
     lookup: bool,
 
@@ -70,8 +216,9 @@ struct Args {
     size: u8,
 
     /// Word obscurity level 1 = everyday, 2 = bigger list, 3 = a lot of weird words
-    #[arg(short, long, default_value_t = 3)]
-    obscurity: u8,
+    /// (normally the biggest list installed)
+    #[arg(short, long)]
+    obscurity: Option<u8>,
 
     /// Reverse string (useful for reverse 'in' clues)
     #[arg(short = 'v', long, default_value_t = false)]
@@ -118,6 +265,99 @@ struct Args {
     /// Remove letters interactively
     #[arg(short, long, default_value_t = false)]
     remove: bool,
+
+    /// Directory containing the word lists and other data files. If not given,
+    /// $LOOKUP_DATA_DIR, the XDG data directories (e.g. ~/.local/share/lookup),
+    /// the directory containing the executable and then the current directory are searched.
+    #[arg(long)]
+    data_dir: Option<std::path::PathBuf>,
+
+    /// Rebuild the binary index files used to speed up loading the word lists.
+    /// These are normally (re)built automatically the first time a list is used.
+    #[arg(long, default_value_t = false)]
+    build_index: bool,
+}
+
+// Library errors are reported here, with the exit codes lookup has always used
+fn unwrap_or_exit<T>(result: Result<T, LookupError>, action: &Action) -> T {
+    match result {
+        Ok(value) => value,
+        Err(err) => {
+            println!("{}", format!("Error: {}", err).red());
+            let code = match err {
+                LookupError::WrongLength { .. } if *action == Action::Wordle => 6,
+                LookupError::WrongLength { .. }
+                    if *action == Action::Jumble || *action == Action::MultiAnagram =>
+                {
+                    7
+                }
+                LookupError::WrongLength { .. } => 3,
+                LookupError::LetterNotInSource(_) => 7,
+                LookupError::BadPattern(_) => 8,
+                LookupError::MissingDataFile { .. } => 10,
+            };
+            exit(code);
+        }
+    }
+}
+
+// Each part of the search string (the words of a clue) followed by
+// whichever synonyms and abbreviations of it are wanted
+fn clue_parts(
+    search_string: &str,
+    synonyms: bool,
+    abbreviations: bool,
+    action: &Action,
+) -> Vec<Vec<String>> {
+    // Either file can be missing, as long as we have one of those wanted
+    let found = |file_name: &str| file::load::data_path(file_name).is_ok();
+    let synonyms_found = synonyms && found("thesaurus.txt");
+    let abbreviations_found = abbreviations && found("abbreviations.txt");
+    if (synonyms || abbreviations) && !synonyms_found && !abbreviations_found {
+        let file_name = match synonyms {
+            true => "thesaurus.txt",
+            false => "abbreviations.txt",
+        };
+        unwrap_or_exit(file::load::data_path(file_name), action);
+    }
+    let mut parts: Vec<Vec<String>> = Vec::new();
+    // Note expand_numbers() has replaced the spaces between the parts with '/'
+    for part in search_string.split('/') {
+        let mut candidates = vec![part.to_string()];
+        if synonyms_found {
+            unwrap_or_exit(file::load::thesaurus(&mut candidates, part), action);
+        }
+        if abbreviations_found {
+            unwrap_or_exit(file::load::abbreviations(&mut candidates, part), action);
+        }
+        parts.push(candidates);
+    }
+    parts
+}
+
+// Says how many words of a codeword or cryptogram had to be left unsolved
+fn show_unsolved(solution: &PartialSolution) {
+    match solution.unsolved.len() {
+        0 => {}
+        1 => println!("{}", "1 word couldn't be solved".yellow()),
+        n => println!("{}", format!("{} words couldn't be solved", n).yellow()),
+    }
+}
+
+// How often words are used in phrases tells the substitution solvers which
+// are commonest, but they can manage without
+fn phrase_uses(debug: bool, action: &Action) -> HashMap<String, usize> {
+    let mut phrases = WordIndex::new();
+    match debug {
+        true => HashMap::new(),
+        false => match file::load::full_list(&mut phrases, "phrases.txt") {
+            Err(LookupError::MissingDataFile { .. }) => HashMap::new(),
+            result => {
+                unwrap_or_exit(result, action);
+                word_uses(&phrases)
+            }
+        },
+    }
 }
 
 fn main() {
@@ -131,27 +371,83 @@ fn main() {
     let mut cmd = Args::command();
     let args = Args::parse();
 
-    if args.obscurity < 1 || args.obscurity > 3 {
+    if args
+        .obscurity
+        .is_some_and(|level| !(1..=3).contains(&level))
+    {
         println!("{}", "\nError: Invalid word obscurity level".red());
         let _ = cmd.print_help();
         exit(10);
     }
 
+    if let Some(dir) = &args.data_dir {
+        file::load::set_data_dir(dir);
+    }
+
+    if args.build_index {
+        let mut found = false;
+        for file_name in ["words_1.txt", "words_2.txt", "words_3.txt", "phrases.txt"] {
+            // Not every list ships (e.g. words_3.txt), so just index the ones we have
+            let Ok(path) = file::load::data_path(file_name) else {
+                continue;
+            };
+            found = true;
+            match file::cache::build(&path) {
+                Ok(count) => println!("Indexed {} ({} entries)", path.display(), count),
+                Err(e) => println!("{}", format!("Skipped {}: {}", path.display(), e).yellow()),
+            }
+        }
+        if !found {
+            // Report where we looked for the everyday list
+            unwrap_or_exit(file::load::data_path("words_1.txt"), &Action::Undefined);
+        }
+        exit(0);
+    }
+
     if !args.define.is_empty() && !args.define[0].is_empty() {
         let combined = args.define.join(" ").to_lowercase();
         let mut output_type = OutputType::Normal;
         if args.json {
             output_type = OutputType::Json;
         }
-        define(&combined, output_type);
+        unwrap_or_exit(define(&combined, output_type), &Action::Undefined);
+        exit(0);
+    }
+
+    if !args.abbrev.is_empty() && !args.abbrev[0].is_empty() {
+        let combined = args.abbrev.join(" ").to_lowercase();
+        let mut output_type = OutputType::Normal;
+        if args.json {
+            output_type = OutputType::Json;
+        }
+        unwrap_or_exit(abbreviate(&combined, output_type), &Action::Abbreviation);
+        exit(0);
+    }
+
+    if !args.indicator.is_empty() && !args.indicator[0].is_empty() {
+        let combined = args.indicator.join(" ").to_lowercase();
+        let mut output_type = OutputType::Normal;
+        if args.json {
+            output_type = OutputType::Json;
+        }
+        unwrap_or_exit(indicator(&combined, output_type), &Action::Indicator);
         exit(0);
     }
 
     let mut phrase_lookup = false;
     // The search string can be multiple words, if it is we infer it's a phrase lookup.
+    // (Unless it's clue "fodder" which is searched for words, or letters to
+    // anagram into several words.)
     let mut search_string = "".to_string();
     if args.search_string.len() > 1 {
-        phrase_lookup = true;
+        phrase_lookup = !args.hidden
+            && !args.regular_words
+            && !args.container
+            && !args.charade
+            && !args.multiword
+            && args.enumeration.is_empty()
+            && args.minus.is_empty()
+            && !args.subanagram;
         for word in args.search_string {
             if !search_string.is_empty() {
                 search_string += " ";
@@ -162,25 +458,43 @@ fn main() {
         search_string = args.search_string[0].clone().to_lowercase();
     }
 
-    if search_string.is_empty() && args.thesaurus.is_empty() {
+    if search_string.is_empty()
+        && args.thesaurus.is_empty()
+        && args.clue.is_empty()
+        && args.pattern.is_empty()
+        && args.codeword.is_none()
+        && args.cryptogram.is_empty()
+        && args.letterboxed.is_empty()
+        && !args.play
+        && args.feedback.iter().all(|f| f.is_empty())
+    {
         let _ = cmd.print_help();
         exit(1);
     }
 
     // We allow numbers in the search string, these represent the number of "blanks".
     // so for example -f 3f7 would result in "...f......."
+    // A crossword enumeration like "(5,3-4)" works too (except in a regex, where
+    // brackets and commas mean something else)
+    if !args.regex {
+        search_string = expand_enumeration(&search_string);
+    }
     search_string = expand_numbers(&search_string);
+    // Letter patterns use digits (and capitals) as variables rather than blanks
+    if !args.pattern.is_empty() {
+        search_string = args.pattern.clone();
+    }
 
-    let mut file_name = format!("./words_{}.txt", args.obscurity).to_string();
+    let obscurity = args.obscurity.unwrap_or_else(file::load::default_obscurity);
+    let mut file_name = format!("words_{}.txt", obscurity).to_string();
     if args.debug {
         // very small file for testing
-        file_name = "./words_debug.txt".to_string();
+        file_name = "words_debug.txt".to_string();
     }
     if phrase_lookup {
-        file_name = "./phrases.txt".to_string();
+        file_name = "phrases.txt".to_string();
     }
-    let mut anagrams: HashMap<String, Vec<usize>> = HashMap::new();
-    let mut word_list: Vec<String> = Vec::new();
+    let mut index = WordIndex::new();
     let mut thesaurus: Vec<String> = Vec::new();
     let mut lookup_mode = false;
     if phrase_lookup && !args.lookup {
@@ -192,19 +506,6 @@ fn main() {
         exit(0);
     }
 
-    // Word list file must exist in the current path
-    let mut vec_index: usize = 0usize;
-    if args.wordle {
-        file::load::wordle(&mut word_list, &file_name);
-    } else {
-        file::load::full_list(&mut word_list, &mut anagrams, &file_name, &mut vec_index);
-    }
-
-    // Also read in thesaurus if required
-    if !args.thesaurus.is_empty() {
-        file::load::thesaurus(&mut thesaurus, &(args.thesaurus.to_string()));
-    }
-
     let mut results: Vec<String> = Vec::new();
 
     let mut action: Action = Action::Undefined;
@@ -228,6 +529,18 @@ fn main() {
     if args.regex {
         action = Action::Regex;
     }
+    if !args.pattern.is_empty() {
+        action = Action::LetterPattern;
+    }
+    if args.codeword.is_some() {
+        action = Action::Codeword;
+    }
+    if !args.cryptogram.is_empty() {
+        action = Action::Cryptogram;
+    }
+    if !args.letterboxed.is_empty() {
+        action = Action::LetterBoxed;
+    }
     if args.regular && args.reverse {
         action = Action::RegularPatterns;
     } else {
@@ -238,9 +551,30 @@ fn main() {
             action = Action::Reverse;
         }
     }
-    if args.anagram {
+    if args.anagram || !args.minus.is_empty() {
         action = Action::Anagram;
     }
+    if args.multiword || !args.enumeration.is_empty() {
+        action = Action::MultiAnagram;
+    }
+    if args.subanagram {
+        action = Action::Subanagram;
+    }
+    if args.hidden {
+        action = Action::Hidden;
+    }
+    if args.regular_words {
+        action = Action::RegularWords;
+    }
+    if args.container {
+        action = Action::Container;
+    }
+    if args.charade {
+        action = Action::Charade;
+    }
+    if !args.clue.is_empty() {
+        action = Action::Clue;
+    }
     if search_string.contains('%') {
         action = Action::Lookup;
     }
@@ -248,7 +582,7 @@ fn main() {
     // is required from the input
     if action == Action::Undefined {
         let mut msg = String::from("No game type specified, assuming ");
-        if lookup_mode || search_string.contains('_') || search_string.contains('.') {
+        if lookup_mode || search_string.contains(['_', '.', '[', '@', '#']) {
             action = Action::Lookup;
             msg += "lookup";
         } else {
@@ -269,44 +603,288 @@ fn main() {
         }
     }
 
+    if !args.minus.is_empty()
+        && (action == Action::Anagram
+            || action == Action::MultiAnagram
+            || action == Action::Subanagram)
+    {
+        search_string = unwrap_or_exit(remove_letters(&search_string, &args.minus), &action);
+    }
+
+    // Actions which search the search string for words
+    let fodder = action == Action::Hidden || action == Action::RegularWords;
+    // Actions which build answers from parts of the search string
+    let builds_answers = fodder
+        || action == Action::Container
+        || action == Action::Charade
+        || action == Action::Clue;
+
+    // Word list files are found with file::load::data_path(). Some actions only work
+    // on the search string itself, so don't need it at all.
+    let needs_word_list = action != Action::Jumble
+        && action != Action::Thesaurus
+        && action != Action::RegexWithThesaurus
+        && action != Action::RegularPatterns
+        && action != Action::Reverse;
+    // Wordle can be played with longer or shorter words, given by --length or else
+    // by the search string or the guesses
+    let wordle_length = args.length.unwrap_or(match search_string.is_empty() {
+        true => args
+            .feedback
+            .iter()
+            .find_map(|f| f.split_once(':').map(|(guess, _)| guess.len()))
+            .unwrap_or(5),
+        false => search_string.len(),
+    });
+    if action == Action::Wordle && !WORDLE_LENGTHS.contains(&wordle_length) {
+        println!("{}", "Error: Wordle words must have 4 to 11 letters".red());
+        exit(6);
+    }
+    if action == Action::Wordle {
+        unwrap_or_exit(
+            file::load::wordle(&mut index, &file_name, wordle_length),
+            &action,
+        );
+    } else if needs_word_list {
+        // Substitution puzzles try the everyday words first, so load them first
+        if (action == Action::Codeword || action == Action::Cryptogram)
+            && !args.debug
+            && file_name != "words_1.txt"
+        {
+            unwrap_or_exit(file::load::full_list(&mut index, "words_1.txt"), &action);
+        }
+        unwrap_or_exit(file::load::full_list(&mut index, &file_name), &action);
+    }
+
+    // Also read in thesaurus if required
+    if !args.thesaurus.is_empty() {
+        unwrap_or_exit(
+            file::load::thesaurus(&mut thesaurus, &(args.thesaurus.to_string())),
+            &action,
+        );
+    }
+
     // Also add phrases to the word list
-    // unless excluded or game type is wordle, spellingbee, or panagram
+    // unless excluded or game type is wordle, spellingbee, panagram or subanagram
     if !args.excludephrases
         && !args.debug
+        && !phrase_lookup
+        && needs_word_list
         && action != Action::Spellingbee
         && action != Action::Panagram
+        && action != Action::Subanagram
         && action != Action::Wordle
+        && action != Action::Codeword
+        && action != Action::Cryptogram
+        && action != Action::LetterBoxed
     {
-        file_name = "./phrases.txt".to_string();
-        file::load::full_list(&mut word_list, &mut anagrams, &file_name, &mut vec_index);
+        file_name = "phrases.txt".to_string();
+        unwrap_or_exit(file::load::full_list(&mut index, &file_name), &action);
     }
 
+    let mut hidden: Vec<HiddenWord> = Vec::new();
+    let mut wordplay: Vec<Wordplay> = Vec::new();
     if action == Action::Panagram {
-        results = panagram(&search_string, &word_list, &anagrams);
+        results = unwrap_or_exit(panagram(&search_string, &index), &action);
+    } else if action == Action::Spellingbee && (args.play || args.hints) {
+        let bee = SpellingBee::new(&search_string, &index);
+        match args.play {
+            true => ui::display::interactive_spellingbee(&bee),
+            false => ui::display::show_spellingbee_hints(&bee, &[]),
+        }
+        exit(0);
     } else if action == Action::Spellingbee {
-        results = spellingbee(&search_string, &word_list, args.debug);
+        results = spellingbee(&search_string, &index, args.debug);
+    } else if action == Action::Wordle && args.play {
+        ui::display::interactive_wordle(&index, wordle_length, args.hard, args.guess_pool);
+        exit(0);
     } else if action == Action::Wordle {
-        if search_string.len() != 5 {
-            println!("Search string is not five characters");
-            exit(6);
+        let mut feedback: Vec<(String, BoardsFeedback)> = Vec::new();
+        for f in args.feedback.iter().filter(|f| !f.is_empty()) {
+            feedback.push(unwrap_or_exit(
+                WordleConstraints::parse_boards_feedback(f),
+                &action,
+            ));
         }
-        results = wordle(&search_string, &word_list, &args.exclude, &args.include);
+        let boards = feedback.first().map_or(1, |(_, colours)| colours.len());
+        if feedback.iter().any(|(_, colours)| colours.len() != boards) {
+            let err = format!(
+                "every --feedback needs the colours for all {} boards",
+                boards
+            );
+            unwrap_or_exit(Err(LookupError::BadPattern(err)), &action)
+        }
+        if boards > 1
+            && (!search_string.is_empty() || !args.include.is_empty() || !args.exclude.is_empty())
+        {
+            let err = "with more than one board, give all the colours with --feedback";
+            unwrap_or_exit(Err(LookupError::BadPattern(err.to_string())), &action)
+        }
+        // The green letters can be left out when the feedback is given instead
+        let greens = match search_string.is_empty() {
+            true => "_".repeat(wordle_length),
+            false => search_string.clone(),
+        };
+        if greens.len() != wordle_length {
+            unwrap_or_exit(
+                Err(LookupError::WrongLength {
+                    expected: wordle_length,
+                    actual: greens.len(),
+                }),
+                &action,
+            )
+        }
+        let constraints = unwrap_or_exit(
+            WordleConstraints::from_search(&greens, &args.exclude, &args.include),
+            &action,
+        );
+        let mut constraints = vec![constraints; boards];
+        for (guess, colours) in &feedback {
+            for (board, tiles) in constraints.iter_mut().zip(colours) {
+                if let Some(tiles) = tiles {
+                    unwrap_or_exit(board.add_feedback(guess, tiles), &action);
+                }
+            }
+        }
+        if let Some(count) = args.suggest {
+            let unsolved: Vec<&WordleConstraints> =
+                constraints.iter().filter(|c| !c.is_solved()).collect();
+            let answers: Vec<Vec<String>> = unsolved.iter().map(|c| c.candidates(&index)).collect();
+            // In hard mode guesses must keep the green and yellow letters,
+            // but may still use grey ones
+            let guesses: Vec<String> = index
+                .words()
+                .iter()
+                .filter(|guess| !args.hard || unsolved.iter().all(|c| c.allows_in_hard_mode(guess)))
+                .cloned()
+                .collect();
+            let suggestions =
+                suggest_guesses_for_boards(&answers, &guesses, count, args.guess_pool);
+            let counts: Vec<usize> = answers.iter().map(Vec::len).collect();
+            ui::display::show_suggestions(&suggestions, &counts);
+            exit(0);
+        }
+        if boards > 1 {
+            let answers: Vec<Vec<String>> =
+                constraints.iter().map(|c| c.candidates(&index)).collect();
+            ui::display::show_boards(&answers);
+            exit(0);
+        }
+        results = constraints[0].candidates(&index);
     } else if action == Action::Anagram {
-        results = anagram_search(&search_string, &word_list, &anagrams);
-    } else if action == Action::Lookup || action == Action::LookupWithThesaurus {
-        if search_string.contains('%') && search_string.find('%') != Some(search_string.len() - 1) {
-            println!("Error: '%' wildcard must only be used at end of search string");
-            exit(8);
+        results = anagram_search(&search_string, &index);
+    } else if action == Action::Hidden {
+        hidden = hidden_words(&search_string, &index, 3);
+        results = hidden.iter().map(|h| h.word.clone()).collect();
+    } else if action == Action::RegularWords {
+        hidden = regular_words(&search_string, &index, 3);
+        results = hidden.iter().map(|h| h.word.clone()).collect();
+    } else if action == Action::Container {
+        let parts = clue_parts(&search_string, args.synonyms, false, &action);
+        if parts.len() != 2 {
+            unwrap_or_exit(
+                Err(LookupError::BadPattern(
+                    "--container needs two parts, e.g. in pat".to_string(),
+                )),
+                &action,
+            )
         }
-        results = lookup(&search_string, &word_list, "");
+        wordplay = containers(&parts[0], &parts[1], &index);
+        results = wordplay.iter().map(|w| w.answer.clone()).collect();
+    } else if action == Action::Charade {
+        let parts = clue_parts(&search_string, true, true, &action);
+        wordplay = charades(&parts, &index);
+        results = wordplay.iter().map(|w| w.answer.clone()).collect();
+    } else if action == Action::Clue {
+        let (words, enumeration) = unwrap_or_exit(parse_clue(&args.clue), &action);
+        let indicator_map = unwrap_or_exit(file::load::indicator_map(), &action);
+        let indicators = find_indicators(&words, &indicator_map);
+        // The thesaurus is optional here, without it we just can't check definitions
+        let thesaurus = match file::load::keyed_map("thesaurus.txt") {
+            Err(LookupError::MissingDataFile { .. }) => KeyedEntries::new(),
+            result => unwrap_or_exit(result, &action),
+        };
+        let abbreviations = unwrap_or_exit(file::load::keyed_map("abbreviations.txt"), &action);
+        wordplay = analyse_clue(
+            &words,
+            &enumeration,
+            &indicators,
+            &thesaurus,
+            &abbreviations,
+            &index,
+        );
+        results = wordplay.iter().map(|w| w.answer.clone()).collect();
+    } else if action == Action::Subanagram {
+        let max_length = match args.max_length {
+            0 => search_string.len(),
+            length => length,
+        };
+        results = subanagrams(
+            &search_string,
+            &index,
+            args.min_length,
+            max_length,
+            &args.mandatory.to_lowercase(),
+        );
+    } else if action == Action::MultiAnagram {
+        let enumeration = unwrap_or_exit(parse_enumeration(&args.enumeration), &action);
+        results = unwrap_or_exit(
+            multi_anagram(
+                &search_string,
+                &index,
+                &enumeration,
+                &args.found,
+                args.max_words,
+            ),
+            &action,
+        );
+    } else if action == Action::Lookup || action == Action::LookupWithThesaurus {
+        results = unwrap_or_exit(lookup(&search_string, &index, ""), &action);
         if action == Action::LookupWithThesaurus {
             // we need to remove any words which don't exist in the 'thesaurus' vector
             results.retain(|item| thesaurus.contains(item));
         }
+    } else if action == Action::Codeword {
+        let path = args.codeword.as_ref().unwrap();
+        let mut codeword = unwrap_or_exit(file::load::codeword(path), &action);
+        let given = unwrap_or_exit(Codeword::parse_given(&args.key), &action);
+        codeword.given.extend(given);
+        let uses = phrase_uses(args.debug, &action);
+        match partial_substitution(&codeword.words(), &codeword.given, &index, &uses) {
+            Some(solution) => {
+                ui::display::show_codeword(&codeword, &solution.key);
+                show_unsolved(&solution);
+            }
+            None => {
+                println!("{}", "No solution found".yellow());
+                ui::display::show_codeword(&codeword, &codeword.given);
+            }
+        }
+        exit(0);
+    } else if action == Action::Cryptogram {
+        let cryptogram = Cryptogram::new(&args.cryptogram);
+        let given = unwrap_or_exit(Cryptogram::parse_given(&args.key), &action);
+        let uses = phrase_uses(args.debug, &action);
+        match partial_substitution(&cryptogram.words(), &given, &index, &uses) {
+            Some(solution) => {
+                ui::display::show_cryptogram(&cryptogram, &solution);
+                show_unsolved(&solution);
+            }
+            None => println!("{}", "No solution found".yellow()),
+        }
+        exit(0);
+    } else if action == Action::LetterBoxed {
+        let sides = unwrap_or_exit(parse_letterboxed(&args.letterboxed), &action);
+        let chains = letterboxed(&sides, &index, args.max_words);
+        ui::display::show_letterboxed(&chains, args.max_words);
+        exit(0);
+    } else if action == Action::LetterPattern {
+        results = unwrap_or_exit(variable_lookup(&search_string, &index), &action);
     } else if action == Action::Regex {
-        results = regex_lookup(&search_string, &word_list);
+        results = unwrap_or_exit(regex_lookup(&search_string, &index), &action);
     } else if action == Action::RegexWithThesaurus {
-        results = regex_lookup(&search_string, &thesaurus);
+        let thesaurus = WordIndex::from_words(thesaurus);
+        results = unwrap_or_exit(regex_lookup(&search_string, &thesaurus), &action);
     } else if action == Action::Jumble {
         let mut letters = args.found.clone();
         letters = expand_found_string(&search_string, &letters);
@@ -325,11 +903,14 @@ fn main() {
         if args.json {
             output_type = OutputType::Json;
         }
-        jumble(
-            &search_string.to_uppercase(),
-            &letters.to_uppercase(),
-            args.size,
-            output_type,
+        unwrap_or_exit(
+            jumble(
+                &search_string.to_uppercase(),
+                &letters.to_uppercase(),
+                args.size,
+                output_type,
+            ),
+            &action,
         );
         if output_type != OutputType::Json {
             println!();
@@ -345,16 +926,33 @@ fn main() {
     if args.size > 0 {
         results = remove_wrong_sized_words(&results, args.size);
     }
-    if !args.found.is_empty() && !search_string.is_empty() {
+    if builds_answers && !args.enumeration.is_empty() {
+        let enumeration = unwrap_or_exit(parse_enumeration(&args.enumeration), &action);
+        results.retain(|word| matches_enumeration(word, &enumeration));
+    }
+    if action == Action::MultiAnagram {
+        // The found letters have already been matched against each word
+    } else if !args.found.is_empty() && !search_string.is_empty() && !builds_answers {
         // If the found string is smaller than the search_string then
         // we assume it's an incomplete found string and pad it out
         let found = expand_found_string(&search_string, &args.found);
-        results = remove_found_mismatches(&results, found, args.excludephrases);
+        results = unwrap_or_exit(
+            remove_found_mismatches(&results, found, args.excludephrases),
+            &action,
+        );
     } else if !args.found.is_empty() {
-        results = remove_found_mismatches(&results, args.found, args.excludephrases);
+        results = unwrap_or_exit(
+            remove_found_mismatches(&results, args.found, args.excludephrases),
+            &action,
+        );
     }
 
-    results.sort();
+    if action == Action::Subanagram {
+        // Keep these grouped by length
+        results.sort_by(|a, b| a.len().cmp(&b.len()).then(a.cmp(b)));
+    } else {
+        results.sort();
+    }
     let mut output_type: OutputType = OutputType::Normal;
     if args.json {
         output_type = OutputType::Json;
@@ -365,6 +963,24 @@ fn main() {
     if args.json {
         output_type = OutputType::Json;
     }
+    if fodder {
+        hidden.retain(|h| results.contains(&h.word));
+        ui::display::show_hidden(&search_string, &hidden, output_type);
+        exit(0);
+    }
+    if action == Action::Container || action == Action::Charade || action == Action::Clue {
+        wordplay.retain(|w| results.contains(&w.answer));
+        ui::display::show_wordplay(&wordplay, &search_string, action, output_type);
+        exit(0);
+    }
+    let spellingbee_summary = action == Action::Spellingbee && output_type == OutputType::Normal;
     ui::display::show_results(&results, &search_string, action, output_type);
+    if spellingbee_summary {
+        let bee = SpellingBee {
+            letters: search_string,
+            words: results,
+        };
+        ui::display::show_spellingbee_summary(&bee);
+    }
     exit(0);
 }
@@ -2,15 +2,29 @@ pub mod display {
 
     use crate::anagram_search;
     use crate::define;
+    use crate::enumeration;
     use crate::file;
     use crate::file::load::thesaurus;
     use crate::jumble;
     use crate::lookup;
+    use crate::ordinal;
     use crate::regular_patterns;
     use crate::remove_found_mismatches;
     use crate::reverse;
+    use crate::suggest_guesses;
     use crate::Action;
+    use crate::Codeword;
+    use crate::Cryptogram;
+    use crate::GuessScore;
+    use crate::HiddenWord;
+    use crate::LookupError;
     use crate::OutputType;
+    use crate::PartialSolution;
+    use crate::SpellingBee;
+    use crate::Tile;
+    use crate::WordIndex;
+    use crate::WordleConstraints;
+    use crate::Wordplay;
     use std::collections::HashMap;
     use std::collections::HashSet;
     use std::io::stdout;
@@ -91,12 +105,14 @@ pub mod display {
         if output_type == OutputType::Json {
             let json_output = serde_json::to_string(&results).unwrap();
             println!("{}", json_output);
+        } else if action == Action::Subanagram && output_type == OutputType::Normal {
+            show_by_length(results);
         } else {
             for word in results {
                 if word.contains(char::is_whitespace) && output_type != OutputType::Narrow {
                     print!("'");
                 }
-                if (action == Action::Panagram && word.len() == 9)
+                if (action == Action::Panagram && word.len() == search_string.len())
                     || (action == Action::Spellingbee
                         && word_contains_all_letters(word, search_string))
                 {
@@ -107,12 +123,372 @@ pub mod display {
                 if word.contains(char::is_whitespace) && output_type != OutputType::Narrow {
                     print!("'");
                 }
+                // Show the enumeration of phrases, e.g. 'a dead duck' (1,4,4)
+                if word.contains(|c: char| !c.is_alphabetic()) && output_type == OutputType::Normal
+                {
+                    print!(" ({})", enumeration(word));
+                }
                 print_separator(output_type);
             }
             println!();
         }
     }
 
+    /// Lists hidden words, each alongside the fodder with the word's letters
+    /// highlighted, e.g. "thai    birTHAIm"
+    pub fn show_hidden(fodder: &str, hidden: &[HiddenWord], output_type: OutputType) {
+        if output_type != OutputType::Normal {
+            let words: Vec<String> = hidden.iter().map(|h| h.word.clone()).collect();
+            show_results(&words, fodder, Action::Hidden, output_type);
+            return;
+        }
+        let width = hidden.iter().map(|h| h.word.len()).max().unwrap_or(0);
+        for h in hidden {
+            print!("{:<width$}  ", h.word.yellow(), width = width);
+            let mut letter_pos = 0;
+            for c in fodder.chars() {
+                if c == '/' {
+                    print!(" ");
+                    continue;
+                }
+                if !c.is_ascii_alphabetic() {
+                    print!("{}", c);
+                    continue;
+                }
+                if letter_pos >= h.start
+                    && letter_pos < h.end
+                    && (letter_pos - h.start) % h.step == 0
+                {
+                    print!("{}", c.to_ascii_uppercase().to_string().bold());
+                } else {
+                    print!("{}", c.to_ascii_lowercase());
+                }
+                letter_pos += 1;
+            }
+            if h.step > 1 {
+                print!(" (every {} letter)", ordinal(h.step));
+            }
+            if h.reversed {
+                print!(" (reversed)");
+            }
+            println!();
+        }
+    }
+
+    /// Prints a codeword grid with whatever letters are known, then the key
+    pub fn show_codeword(codeword: &Codeword, key: &HashMap<usize, char>) {
+        for row in &codeword.cells {
+            for cell in row {
+                match cell {
+                    None => print!(" {}", "#".dimmed()),
+                    Some(number) => match key.get(number) {
+                        Some(letter) if codeword.given.contains_key(number) => {
+                            print!(" {}", letter.to_ascii_uppercase().to_string().bold())
+                        }
+                        Some(letter) => print!(" {}", letter.to_ascii_uppercase()),
+                        None => print!(" ?"),
+                    },
+                }
+            }
+            println!();
+        }
+        println!();
+        for numbers in [1..=13, 14..=26] {
+            for number in numbers.clone() {
+                print!("{:>3}", number.to_string().yellow());
+            }
+            println!();
+            for number in numbers {
+                let letter = key.get(&number).map_or('-', |l| l.to_ascii_uppercase());
+                print!("{:>3}", letter);
+            }
+            println!();
+        }
+    }
+
+    /// Prints a cryptogram a line at a time with the decoded text beneath it,
+    /// unsolved words in red and unknown letters as '?', then the key
+    pub fn show_cryptogram(cryptogram: &Cryptogram, solution: &PartialSolution) {
+        let decode = |c: char| match c.is_ascii_alphabetic() {
+            true => {
+                let symbol = (c.to_ascii_uppercase() as u8 - b'A') as usize;
+                solution.key.get(&symbol).copied().unwrap_or('?')
+            }
+            false => c,
+        };
+        // Each word of the text, and whether it was solved
+        let mut words: Vec<(&str, bool)> = Vec::new();
+        let mut position = 0;
+        for word in cryptogram.text.split_whitespace() {
+            let mut solved = true;
+            for part in word.split('-') {
+                if part.chars().any(|c| c.is_ascii_alphabetic()) {
+                    solved &= !solution.unsolved.contains(&position);
+                    position += 1;
+                }
+            }
+            words.push((word, solved));
+        }
+        for line in words.chunks(8) {
+            let coded: Vec<String> = line.iter().map(|(w, _)| w.to_uppercase()).collect();
+            println!("{}", coded.join(" ").dimmed());
+            let decoded: Vec<String> = line
+                .iter()
+                .map(|(word, solved)| {
+                    let decoded: String = word.chars().map(decode).collect();
+                    match solved {
+                        true => decoded.normal().to_string(),
+                        false => decoded.red().to_string(),
+                    }
+                })
+                .collect();
+            println!("{}", decoded.join(" "));
+            println!();
+        }
+        let alphabet: Vec<String> = ('A'..='Z').map(|c| c.to_string()).collect();
+        println!("{}", alphabet.join(" ").yellow());
+        let key: Vec<String> = (0..26)
+            .map(|symbol| solution.key.get(&symbol).map_or('-', |c| *c).to_string())
+            .collect();
+        println!("{}", key.join(" "));
+    }
+
+    /// Lists the best Wordle guesses, how much each would tell us, and which
+    /// could be the answer, given how many answers each unsolved board has
+    pub fn show_suggestions(suggestions: &[GuessScore], answers: &[usize]) {
+        match answers {
+            [1] => println!("1 possible answer"),
+            [n] => println!("{} possible answers", n),
+            _ => {
+                let counts: Vec<String> = answers.iter().map(|n| n.to_string()).collect();
+                println!("Possible answers on each board: {}", counts.join(", "));
+            }
+        }
+        for score in suggestions {
+            print!(
+                "  {}  {:.2} bits, {:.1} left on average",
+                score.guess.yellow(),
+                score.entropy,
+                score.expected_remaining
+            );
+            if score.possible_answer {
+                print!("{}", " (possible answer)".green());
+            }
+            println!();
+        }
+    }
+
+    /// Lists the possible answers for each of several Wordle boards
+    pub fn show_boards(answers: &[Vec<String>]) {
+        for (board, words) in answers.iter().enumerate() {
+            print!("{} ", format!("{:>2}:", board + 1).yellow());
+            match words.len() {
+                1 => println!("{}", words[0].to_uppercase().bold()),
+                _ => println!("{}", words.join(" ")),
+            }
+        }
+    }
+
+    /// The number of words and points in a Spelling Bee, and its pangrams
+    pub fn show_spellingbee_summary(bee: &SpellingBee) {
+        println!("\n{} words, {} points", bee.words.len(), bee.total_score());
+        let pangrams: Vec<String> = bee.pangrams().iter().map(|p| p.to_uppercase()).collect();
+        if !pangrams.is_empty() {
+            println!("Pangrams: {}", pangrams.join(" ").bold());
+        }
+    }
+
+    /// Spelling Bee hints, leaving out the words already found: the points for
+    /// each rank, then how many words start with each letter, by length, and
+    /// with each pair of letters
+    pub fn show_spellingbee_hints(bee: &SpellingBee, found: &[String]) {
+        let pangrams = bee.pangrams();
+        let perfect = pangrams
+            .iter()
+            .filter(|p| p.len() == bee.letters.len())
+            .count();
+        println!(
+            "{} words, {} points, {} pangrams ({} perfect)",
+            bee.words.len(),
+            bee.total_score(),
+            pangrams.len(),
+            perfect
+        );
+        let ranks: Vec<String> = bee
+            .ranks()
+            .iter()
+            .map(|(rank, points)| format!("{} {}", rank, points))
+            .collect();
+        println!("{}", ranks.join(", "));
+        println!();
+
+        let grid = bee.grid(found);
+        let mut lengths: Vec<usize> = grid.values().flat_map(|row| row.keys().copied()).collect();
+        lengths.sort();
+        lengths.dedup();
+        print!("  ");
+        for length in &lengths {
+            print!("{:>4}", length.to_string().yellow());
+        }
+        println!("{:>4}", "Σ".yellow());
+        for (letter, row) in &grid {
+            print!("{}:", letter.to_ascii_uppercase().to_string().yellow());
+            for length in &lengths {
+                match row.get(length) {
+                    Some(count) => print!("{:>4}", count),
+                    None => print!("{:>4}", "-"),
+                }
+            }
+            println!("{:>4}", row.values().sum::<usize>());
+        }
+        print!("{}:", "Σ".yellow());
+        for length in &lengths {
+            let total: usize = grid.values().filter_map(|row| row.get(length)).sum();
+            print!("{:>4}", total);
+        }
+        println!(
+            "{:>4}",
+            grid.values().flat_map(|row| row.values()).sum::<usize>()
+        );
+        println!();
+
+        let starts = bee.two_letter_starts(found);
+        let mut first: Option<char> = None;
+        for (start, count) in &starts {
+            if first.is_some() && first != start.chars().next() {
+                println!();
+            }
+            first = start.chars().next();
+            print!("{}-{} ", start.to_uppercase(), count);
+        }
+        println!();
+    }
+
+    /// A Spelling Bee session: enter the words you find (or ? for hints) and
+    /// your points and rank are kept
+    pub fn interactive_spellingbee(bee: &SpellingBee) {
+        let centre = bee.letters.chars().next().unwrap_or(' ');
+        let mut found: Vec<String> = Vec::new();
+        let mut points = 0;
+        loop {
+            println!();
+            print!(
+                "{} ",
+                centre.to_ascii_uppercase().to_string().yellow().bold()
+            );
+            println!("{}", bee.letters[centre.len_utf8()..].to_uppercase());
+            match points {
+                1 => print!("1 point, {}", bee.rank(points).green()),
+                _ => print!("{} points, {}", points, bee.rank(points).green()),
+            }
+            match bee.ranks().iter().find(|(_, needed)| *needed > points) {
+                Some((rank, needed)) => println!(" ({} more for {})", needed - points, rank),
+                None => println!(),
+            }
+            if found.len() == bee.words.len() {
+                println!("All {} words found!", found.len());
+                break;
+            }
+            let word = input_string("Word (? for hints, enter to finish): ", None).to_lowercase();
+            if word.is_empty() {
+                break;
+            }
+            if word == "?" {
+                show_spellingbee_hints(bee, &found);
+                continue;
+            }
+            let problem = if word.len() < 4 {
+                Some("Too short")
+            } else if !word.contains(centre) {
+                Some("Missing the centre letter")
+            } else if !word.chars().all(|c| bee.letters.contains(c)) {
+                Some("Uses other letters")
+            } else if found.contains(&word) {
+                Some("Already found")
+            } else if !bee.words.contains(&word) {
+                Some("Not in the word list")
+            } else {
+                None
+            };
+            if let Some(problem) = problem {
+                println!("{}", problem.red());
+                continue;
+            }
+            let score = bee.score(&word);
+            points += score;
+            match bee.is_pangram(&word) {
+                true => println!("{}", format!("Pangram! +{}", score).yellow().bold()),
+                false => println!("+{}", score),
+            }
+            found.push(word);
+        }
+        println!(
+            "Found {} of {} words, {} of {} points",
+            found.len(),
+            bee.words.len(),
+            points,
+            bee.total_score()
+        );
+    }
+
+    /// Lists Letter Boxed answers, one chain of words to a line
+    pub fn show_letterboxed(chains: &[Vec<String>], max_words: usize) {
+        if chains.is_empty() {
+            println!(
+                "{}",
+                format!(
+                    "No answer in {} words or fewer (see --max-words)",
+                    max_words
+                )
+                .yellow()
+            );
+            return;
+        }
+        for chain in chains {
+            let words: Vec<String> = chain.iter().map(|w| w.to_uppercase()).collect();
+            let letters: usize = chain.iter().map(String::len).sum();
+            println!("{}  ({} letters)", words.join(" - ").yellow(), letters);
+        }
+    }
+
+    /// Lists answers built from parts of a clue, each with how it was built,
+    /// e.g. "paint  in in pat: pa(in)t"
+    pub fn show_wordplay(
+        wordplay: &[Wordplay],
+        search_string: &str,
+        action: Action,
+        output_type: OutputType,
+    ) {
+        if output_type != OutputType::Normal {
+            let words: Vec<String> = wordplay.iter().map(|w| w.answer.clone()).collect();
+            show_results(&words, search_string, action, output_type);
+            return;
+        }
+        let width = wordplay.iter().map(|w| w.answer.len()).max().unwrap_or(0);
+        for w in wordplay {
+            println!(
+                "{:<width$}  {}",
+                w.answer.yellow(),
+                w.explanation,
+                width = width
+            );
+        }
+    }
+
+    // One line per word length, e.g. "5: aster rates stare"
+    fn show_by_length(results: &[String]) {
+        let mut lengths: Vec<usize> = results.iter().map(|word| word.len()).collect();
+        lengths.sort();
+        lengths.dedup();
+        for length in lengths {
+            print!("{}", format!("{:>2}:", length).yellow());
+            for word in results.iter().filter(|word| word.len() == length) {
+                print!(" {}", word);
+            }
+            println!();
+        }
+    }
+
     pub fn anagram_helper(
         found_letters: &str,
         chars: Vec<char>,
@@ -197,7 +573,7 @@ pub mod display {
             flush();
             let k = get_key();
             match k {
-                KeyPress::Letter(c @ 'A'..'Z') => {
+                KeyPress::Letter(c @ 'A'..='Z') => {
                     if let Some(pos) = s.find(c) {
                         s.remove(pos);
                         removed.push(c);
@@ -216,14 +592,10 @@ pub mod display {
                     break;
                 }
                 KeyPress::Special(SpecialKey::LeftArrow) => {
-                    if current_col > 0 {
-                        current_col -= 1;
-                    }
+                    current_col = current_col.saturating_sub(1);
                 }
-                KeyPress::Special(SpecialKey::RightArrow) => {
-                    if current_col < s.len() as u16 - 1 {
-                        current_col += 1;
-                    }
+                KeyPress::Special(SpecialKey::RightArrow) if current_col < s.len() as u16 - 1 => {
+                    current_col += 1;
                 }
                 KeyPress::Special(SpecialKey::Enter) => {
                     let ch = s[current_col as usize..current_col as usize + 1]
@@ -240,6 +612,130 @@ pub mod display {
         println!("{}", removed.yellow());
     }
 
+    /// A Wordle session: enter each guess, then mark its tiles with G (green),
+    /// Y (yellow) or space (grey), and the possible answers and best next
+    /// guesses are shown (scoring `pool` guesses, as for suggest_guesses())
+    pub fn interactive_wordle(index: &WordIndex, length: usize, hard: bool, pool: usize) {
+        let mut constraints = WordleConstraints::new(length);
+        let mut guessed = 0;
+        let mut answers: Vec<String> = index.words().to_vec();
+        // The suggestions only change when the answers do, so they're worked
+        // out once for each (and not again after a mistyped guess)
+        let mut suggestions: Option<Vec<GuessScore>> = None;
+        loop {
+            show_wordle_answers(&answers);
+            if answers.len() > 1 {
+                let suggestions = suggestions.get_or_insert_with(|| {
+                    println!("Thinking...");
+                    let guesses: Vec<String> = index
+                        .words()
+                        .iter()
+                        .filter(|guess| !hard || constraints.allows_in_hard_mode(guess))
+                        .cloned()
+                        .collect();
+                    suggest_guesses(&answers, &guesses, 5, pool)
+                });
+                show_suggestions(suggestions, &[answers.len()]);
+            }
+            println!();
+            let guess = input_string("Guess (enter to finish): ", None).to_lowercase();
+            if guess.is_empty() {
+                break;
+            }
+            if guess.len() != length || !guess.chars().all(|c| c.is_ascii_lowercase()) {
+                println!("{}", format!("Guesses must be {} letters", length).red());
+                continue;
+            }
+            if hard && !constraints.allows_in_hard_mode(&guess) {
+                println!("{}", "That guess isn't allowed in hard mode".red());
+                continue;
+            }
+            let Some(tiles) = input_tiles(&guess) else {
+                continue;
+            };
+            guessed += 1;
+            if tiles.iter().all(|tile| *tile == Tile::Green) {
+                println!("Solved in {}", guessed);
+                break;
+            }
+            let mut updated = constraints.clone();
+            let _ = updated.add_feedback(&guess, &tiles);
+            let remaining: Vec<String> = answers
+                .iter()
+                .filter(|answer| updated.matches(answer))
+                .cloned()
+                .collect();
+            if remaining.is_empty() {
+                println!(
+                    "{}",
+                    "No words fit that, so it's been ignored - check the colours".red()
+                );
+                guessed -= 1;
+            } else {
+                constraints = updated;
+                answers = remaining;
+                suggestions = None;
+            }
+        }
+    }
+
+    // The possible answers, once there are few enough to read
+    fn show_wordle_answers(answers: &[String]) {
+        println!();
+        if answers.len() <= 30 {
+            println!("{}", answers.join(" ").yellow());
+        }
+    }
+
+    // Shows the guess as tiles to be coloured, returning None if abandoned
+    fn input_tiles(guess: &str) -> Option<Vec<Tile>> {
+        let letters: Vec<char> = guess.to_uppercase().chars().collect();
+        let mut tiles = vec![Tile::Grey; letters.len()];
+        let mut current: usize = 0;
+        loop {
+            crossterm_clear_line();
+            show_tiles(&letters, &tiles);
+            print!("  G/Y/space to colour, enter when done, esc to cancel");
+            crossterm_move_to_column(current as u16 * 3 + 1);
+            flush();
+            match get_key() {
+                KeyPress::Letter('G') => tiles[current] = Tile::Green,
+                KeyPress::Letter('Y') => tiles[current] = Tile::Yellow,
+                KeyPress::Special(SpecialKey::Space) => tiles[current] = Tile::Grey,
+                KeyPress::Special(SpecialKey::LeftArrow) => {
+                    current = current.saturating_sub(1);
+                    continue;
+                }
+                KeyPress::Special(SpecialKey::RightArrow) => {}
+                KeyPress::Special(SpecialKey::Enter) => break,
+                KeyPress::Special(SpecialKey::Escape) => {
+                    crossterm_clear_line();
+                    return None;
+                }
+                _ => {
+                    beep();
+                    continue;
+                }
+            }
+            current = (current + 1).min(letters.len() - 1);
+        }
+        crossterm_clear_line();
+        show_tiles(&letters, &tiles);
+        println!();
+        Some(tiles)
+    }
+
+    fn show_tiles(letters: &[char], tiles: &[Tile]) {
+        for (letter, tile) in letters.iter().zip(tiles) {
+            let shown = format!(" {} ", letter).black();
+            match tile {
+                Tile::Green => print!("{}", shown.on_green()),
+                Tile::Yellow => print!("{}", shown.on_yellow()),
+                Tile::Grey => print!("{}", shown.on_bright_black()),
+            }
+        }
+    }
+
     fn beep() {
         // Print the bell symbol
         print!("{}", 0x07 as char);
@@ -320,7 +816,7 @@ pub mod display {
         match code {
             KeyCode::Char(' ') => KeyPress::Special(SpecialKey::Space),
             KeyCode::Char(c) if c.is_ascii_alphabetic() => KeyPress::Letter(c.to_ascii_uppercase()),
-            KeyCode::Char(c) if c == '/' => KeyPress::Letter('/'),
+            KeyCode::Char('/') => KeyPress::Letter('/'),
             KeyCode::Left => KeyPress::Special(SpecialKey::LeftArrow),
             KeyCode::Right => KeyPress::Special(SpecialKey::RightArrow),
             KeyCode::Esc => KeyPress::Special(SpecialKey::Escape),
@@ -344,12 +840,10 @@ pub mod display {
             .unwrap();
         rl.set_edit_mode(rustyline::EditMode::Vi);
         let mut rc = "".to_string();
-        let readline;
-        if default.is_none() {
-            readline = rl.readline(prompt);
-        } else {
-            readline = rl.readline_with_initial(prompt, ("", default.unwrap()));
-        }
+        let readline = match default {
+            None => rl.readline(prompt),
+            Some(default) => rl.readline_with_initial(prompt, ("", default)),
+        };
         match readline {
             Ok(line) => {
                 rl.add_history_entry(line.as_str()).unwrap();
@@ -386,9 +880,7 @@ pub mod display {
         print!("\n{}  ", prompt);
         crossterm_save_pos();
         if length > result.len() {
-            for _ in result.len()..length {
-                result.push('.');
-            }
+            result.resize(length, '.');
         }
         let mut pos: usize = 0;
         loop {
@@ -412,12 +904,12 @@ pub mod display {
             flush();
             let k = get_key();
             match k {
-                KeyPress::Letter(c @ 'A'..'Z') => {
+                KeyPress::Letter(c @ 'A'..='Z') => {
                     if result[pos] == '/' {
                         beep();
                     } else
                     // empty "allowed" string means no restrictions
-                    if allowed.len() == 0 {
+                    if allowed.is_empty() {
                         result[pos] = c;
                         if pos < result.len() - 1 {
                             pos += 1;
@@ -436,15 +928,11 @@ pub mod display {
                         beep();
                     }
                 }
-                KeyPress::Special(SpecialKey::RightArrow) => {
-                    if pos < result.len() - 1 {
-                        pos += 1;
-                    }
+                KeyPress::Special(SpecialKey::RightArrow) if pos < result.len() - 1 => {
+                    pos += 1;
                 }
                 KeyPress::Special(SpecialKey::LeftArrow) => {
-                    if pos > 0 {
-                        pos -= 1;
-                    }
+                    pos = pos.saturating_sub(1);
                 }
                 KeyPress::Special(SpecialKey::Enter) => {
                     break;
@@ -462,17 +950,13 @@ pub mod display {
                     }
                     result[pos] = '.';
                     if sp == KeyPress::Special(SpecialKey::Backspace) {
-                        if pos > 0 {
-                            pos -= 1;
-                        }
+                        pos = pos.saturating_sub(1);
                     }
                 }
                 KeyPress::Letter('/') => {
                     // Word separators are not allowed on top of existing word
                     // separators or at the very beginning
-                    if result[pos] == '/' {
-                        beep();
-                    } else if pos == 0 || result[pos - 1] == '/' {
+                    if result[pos] == '/' || pos == 0 || result[pos - 1] == '/' {
                         beep();
                     } else {
                         result.insert(pos, '/');
@@ -489,6 +973,7 @@ pub mod display {
         use crate::expand_found_string;
 
         let mut data: HashMap<String, Datum> = HashMap::new();
+        let mut index: Option<WordIndex> = None;
         println!();
         'outer: loop {
             let mut clue = "".to_string();
@@ -563,8 +1048,9 @@ pub mod display {
                     match k {
                         // 'X' is used for testing... TODO delete this
                         KeyPress::Letter('X') => {
-                            let foo = input_string_new("Enter stuff:", Some(&""), 9, "CELEBRATE");
-                            println!("{}", foo);
+                            let entered =
+                                input_string_new("Enter stuff:", Some(""), 9, "CELEBRATE");
+                            println!("{}", entered);
                         }
                         KeyPress::Letter('J') => {
                             println!();
@@ -574,12 +1060,14 @@ pub mod display {
                             } else {
                                 letters = expand_found_string(&search_string, &letters);
                             }
-                            jumble(
+                            if let Err(e) = jumble(
                                 &search_string,
                                 &letters,
                                 search_string.len() as u8,
                                 OutputType::Normal,
-                            );
+                            ) {
+                                show_error(&e);
+                            }
                             break;
                         }
                         KeyPress::Letter('F') => {
@@ -621,7 +1109,12 @@ pub mod display {
                         KeyPress::Letter('T') => {
                             println!("\nThesaurus: {}", search_string.white().bold());
                             let mut results: Vec<String> = Vec::new();
-                            thesaurus(&mut results, &search_string.to_ascii_lowercase());
+                            if let Err(e) =
+                                thesaurus(&mut results, &search_string.to_ascii_lowercase())
+                            {
+                                show_error(&e);
+                                break;
+                            }
                             let mut first = true;
                             for s in results {
                                 if !first {
@@ -635,26 +1128,15 @@ pub mod display {
                         }
                         KeyPress::Letter('A') => {
                             println!("\nAnagram: {}", search_string.white().bold());
-                            let mut anagrams: HashMap<String, Vec<usize>> = HashMap::new();
-                            let mut word_list: Vec<String> = Vec::new();
-                            let mut vec_index: usize = 0usize;
-                            file::load::full_list(
-                                &mut word_list,
-                                &mut anagrams,
-                                "./words_3.txt",
-                                &mut vec_index,
-                            );
-                            file::load::full_list(
-                                &mut word_list,
-                                &mut anagrams,
-                                "./phrases.txt",
-                                &mut vec_index,
-                            );
-                            let results = anagram_search(
-                                &search_string.to_ascii_lowercase(),
-                                &word_list,
-                                &anagrams,
-                            );
+                            let index = match tui_index(&mut index) {
+                                Ok(index) => index,
+                                Err(e) => {
+                                    show_error(&e);
+                                    break;
+                                }
+                            };
+                            let results =
+                                anagram_search(&search_string.to_ascii_lowercase(), index);
                             for s in results {
                                 println!("* {}", s.yellow());
                             }
@@ -662,49 +1144,41 @@ pub mod display {
                         }
                         KeyPress::Letter('L') => {
                             println!("\nLookup: {}", search_string.white().bold());
-                            let mut anagrams: HashMap<String, Vec<usize>> = HashMap::new();
-                            let mut word_list: Vec<String> = Vec::new();
-                            let file_name = "./words_3.txt";
-                            let mut vec_index: usize = 0usize;
-                            file::load::full_list(
-                                &mut word_list,
-                                &mut anagrams,
-                                file_name,
-                                &mut vec_index,
-                            );
-                            file::load::full_list(
-                                &mut word_list,
-                                &mut anagrams,
-                                "./phrases.txt",
-                                &mut vec_index,
-                            );
-                            let mut results;
-                            if !search_string.is_empty()
+                            let index = match tui_index(&mut index) {
+                                Ok(index) => index,
+                                Err(e) => {
+                                    show_error(&e);
+                                    break;
+                                }
+                            };
+                            let results = if !search_string.is_empty()
                                 && !search_string.contains('.')
                                 && !search_string.contains('_')
                                 && !search_string.contains('%')
                             {
                                 // If we have a non-wildcarded search string, we can do the
                                 // lookup by an anagram search followed by remove_found_mismatches()
-                                results = anagram_search(
-                                    &search_string.to_ascii_lowercase(),
-                                    &word_list,
-                                    &anagrams,
-                                );
-                                results =
-                                    remove_found_mismatches(&results, found_string.clone(), false);
+                                let anagrams =
+                                    anagram_search(&search_string.to_ascii_lowercase(), index);
+                                remove_found_mismatches(&anagrams, found_string.clone(), false)
                             } else {
-                                results =
-                                    lookup(&found_string.to_ascii_lowercase(), &word_list, "");
-                            }
-                            for s in results {
-                                println!("* {}", s.yellow());
+                                lookup(&found_string.to_ascii_lowercase(), index, "")
+                            };
+                            match results {
+                                Ok(results) => {
+                                    for s in results {
+                                        println!("* {}", s.yellow());
+                                    }
+                                }
+                                Err(e) => show_error(&e),
                             }
                             break;
                         }
                         KeyPress::Letter('D') => {
                             println!("\nDefine: {}", search_string.white().bold());
-                            define(&search_string, OutputType::Normal);
+                            if let Err(e) = define(&search_string, OutputType::Normal) {
+                                show_error(&e);
+                            }
                             break;
                         }
                         KeyPress::Letter('V') => {
@@ -792,19 +1266,36 @@ pub mod display {
         Ok(())
     }
 
+    // The word list is only loaded the first time a lookup needs it
+    fn tui_index(index: &mut Option<WordIndex>) -> Result<&WordIndex, LookupError> {
+        if index.is_none() {
+            let mut loaded = WordIndex::new();
+            let file_name = format!("words_{}.txt", file::load::default_obscurity());
+            file::load::full_list(&mut loaded, &file_name)?;
+            file::load::full_list(&mut loaded, "phrases.txt")?;
+            *index = Some(loaded);
+        }
+        Ok(index.as_ref().unwrap())
+    }
+
+    fn show_error(err: &LookupError) {
+        print!("{}", "ERROR: ".bold());
+        println!("{}", err);
+    }
+
     fn save(
         data: &mut HashMap<String, Datum>,
-        clue: &String,
-        search_string: &String,
-        found_string: &String,
-        comment: &String,
+        clue: &str,
+        search_string: &str,
+        found_string: &str,
+        comment: &str,
     ) {
         let d = Datum {
-            comment: comment.clone(),
-            found_string: found_string.clone(),
-            search_string: search_string.clone(),
-            clue: clue.clone(),
+            comment: comment.to_string(),
+            found_string: found_string.to_string(),
+            search_string: search_string.to_string(),
+            clue: clue.to_string(),
         };
-        data.insert(clue.clone(), d);
+        data.insert(clue.to_string(), d);
     }
 }
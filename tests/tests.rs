@@ -1,6 +1,5 @@
-use std::collections::HashMap;
-
 use lookup::*;
+use std::collections::HashMap;
 
 #[test]
 fn test_sort_word() {
@@ -15,110 +14,263 @@ fn test_spellingbee() {
         "cartload".to_string(),
         "frobnish".to_string(),
     ];
-    let results = spellingbee("roldact", &words, false);
+    let results = spellingbee("roldact", &WordIndex::from_words(words), false);
     assert_eq!(results.len(), 2); // should match "doctoral" and "cartload"
 }
 
+#[test]
+fn test_spellingbee_debug() {
+    let words = vec!["doctoral".to_string(), "frobnish".to_string()];
+    let results = spellingbee("roldact", &WordIndex::from_words(words), true);
+    assert_eq!(results, ["doctoral"]);
+}
+
+#[test]
+fn test_spellingbee_scoring() {
+    let words: Vec<String> = [
+        "lace",
+        "pearl",
+        "plectra",
+        "receptacle",
+        "cat",
+        "trap",
+        "tale",
+    ]
+    .iter()
+    .map(|w| w.to_string())
+    .collect();
+    let bee = SpellingBee::new("acpetrl", &WordIndex::from_words(words));
+    assert_eq!(
+        bee.words,
+        ["lace", "pearl", "plectra", "receptacle", "trap", "tale"]
+    );
+    assert_eq!(bee.score("lace"), 1);
+    assert_eq!(bee.score("pearl"), 5);
+    assert_eq!(bee.score("plectra"), 14);
+    assert_eq!(bee.score("receptacle"), 17);
+    assert_eq!(bee.total_score(), 39);
+    assert_eq!(bee.pangrams(), ["plectra", "receptacle"]);
+
+    let ranks = bee.ranks();
+    assert_eq!(ranks[0], ("Beginner", 0));
+    assert_eq!(ranks[1], ("Good Start", 1));
+    assert_eq!(ranks[8], ("Genius", 27));
+    assert_eq!(ranks[9], ("Queen Bee", 39));
+    assert_eq!(bee.rank(0), "Beginner");
+    assert_eq!(bee.rank(27), "Genius");
+    assert_eq!(bee.rank(39), "Queen Bee");
+
+    let found = vec!["pearl".to_string()];
+    let grid = bee.grid(&found);
+    assert_eq!(grid[&'l'][&4], 1);
+    assert_eq!(grid[&'p'][&7], 1);
+    assert!(!grid[&'p'].contains_key(&5));
+    assert_eq!(grid[&'t'][&4], 2);
+    let starts = bee.two_letter_starts(&found);
+    assert_eq!(starts["pl"], 1);
+    assert!(!starts.contains_key("pe"));
+    assert_eq!(starts["ta"], 1);
+    assert_eq!(starts["tr"], 1);
+}
+
 #[test]
 fn test_panagram() {
-    let words = vec!["cartload".to_string(), "plaintiff".to_string()];
-    let mut anagrams: HashMap<String, Vec<usize>> = HashMap::new();
-    anagrams.insert("aacdlort".to_string(), vec![0usize]);
-    anagrams.insert("affiilnpt".to_string(), vec![1usize]);
-    let results = panagram("infaflipt", &words, &anagrams);
+    let words = WordIndex::from_words(vec!["cartload".to_string(), "plaintiff".to_string()]);
+    let results = panagram("infaflipt", &words).unwrap();
     assert_eq!(results.len(), 1); // should match "plaintiff"
 }
 
+#[test]
+fn test_subanagrams() {
+    let words = WordIndex::from_words(vec![
+        "rate".to_string(),
+        "tear".to_string(),
+        "stare".to_string(),
+        "treats".to_string(),
+        "eat".to_string(),
+        "at".to_string(),
+        "sea tear".to_string(),
+    ]);
+    let results = subanagrams("stare", &words, 3, 5, "");
+    assert_eq!(results, vec!["eat", "rate", "tear", "stare"]);
+    // Each letter can only be used once...
+    assert!(!subanagrams("stare", &words, 3, 6, "").contains(&"treats".to_string()));
+    // ...unless there's a blank to make up the difference
+    assert!(subanagrams("stare?", &words, 3, 6, "").contains(&"treats".to_string()));
+    assert_eq!(subanagrams("stare", &words, 2, 5, "s"), vec!["stare"]);
+    assert_eq!(subanagrams("stare", &words, 2, 3, ""), vec!["at", "eat"]);
+    // Panagram works for any size grid
+    let results = panagram("tear", &words).unwrap();
+    assert_eq!(results, vec!["rate", "tear"]);
+}
+
 #[test]
 fn test_anagram_search() {
-    let words = vec!["cartload".to_string(), "plaintiff".to_string()];
-    let mut anagrams: HashMap<String, Vec<usize>> = HashMap::new();
-    anagrams.insert("aacdlort".to_string(), vec![0usize]);
-    anagrams.insert("affiilnpt".to_string(), vec![1usize]);
-    let results = anagram_search("infaflipt", &words, &anagrams);
+    let words = WordIndex::from_words(vec!["cartload".to_string(), "plaintiff".to_string()]);
+    let results = anagram_search("infaflipt", &words);
     assert_eq!(results.len(), 1); // should match "plaintiff"
-    let results2 = anagram_search("frobnish", &words, &anagrams);
+    let results2 = anagram_search("frobnish", &words);
     assert_eq!(results2.len(), 0); // should not match any
 }
 
 #[test]
 fn test_lookup() {
-    let words = vec![
+    let index = WordIndex::from_words(vec![
         "doctoral".to_string(),
         "cartload".to_string(),
         "frobnish".to_string(),
         "frazzled".to_string(),
         "not care".to_string(),
-    ];
-    let results = lookup("f_o_ni__", &words, "");
+    ]);
+    let results = lookup("f_o_ni__", &index, "").unwrap();
     assert_eq!(results.len(), 1); // should match "frobnish"
-    let results2 = lookup("s__v", &words, "");
+    let results2 = lookup("s__v", &index, "").unwrap();
     assert_eq!(results2.len(), 0); // should not match anything
-    let results3 = lookup("fra_____", &words, "z");
+    let results3 = lookup("fra_____", &index, "z").unwrap();
     assert_eq!(results3.len(), 0); // should not match anything
-    let results4 = lookup("not/c___", &words, "z");
+    let results4 = lookup("not/c___", &index, "z").unwrap();
     assert_eq!(results4.len(), 1); // should match "not care"
 }
 
 #[test]
 fn test_lookup_with_wildcard() {
-    let words = vec![
+    let index = WordIndex::from_words(vec![
         "arc".to_string(),
         "arch".to_string(),
         "archimedes".to_string(),
-    ];
-    let results = lookup("arch%", &words, "");
+    ]);
+    let results = lookup("arch%", &index, "").unwrap();
     assert_eq!(results.len(), 2); // should match "arch" and "archimedes" but not shorter words
 }
 
+#[test]
+fn test_lookup_with_inline_wildcards() {
+    let index = WordIndex::from_words(vec![
+        "stresses".to_string(),
+        "stress".to_string(),
+        "ses".to_string(),
+        "sees".to_string(),
+        "not care".to_string(),
+        "not on your nelly".to_string(),
+    ]);
+    let mut results = lookup("s%es", &index, "").unwrap();
+    results.sort();
+    assert_eq!(results, ["sees", "ses", "stresses"]);
+    // After a word break, and across them
+    assert_eq!(lookup("not/c%", &index, "").unwrap(), ["not care"]);
+    assert_eq!(lookup("n%n%y", &index, "").unwrap(), ["not on your nelly"]);
+    assert_eq!(lookup("%ress", &index, "").unwrap(), ["stress"]);
+    let mut results = lookup("%r%", &index, "").unwrap();
+    results.sort();
+    assert_eq!(
+        results,
+        ["not care", "not on your nelly", "stress", "stresses"]
+    );
+    // Excluded letters can't be matched by a wildcard
+    let mut results = lookup("s%s", &index, "t").unwrap();
+    results.sort();
+    assert_eq!(results, ["sees", "ses"]);
+
+    let pattern = Pattern::compile("c_mp%r").unwrap();
+    assert_eq!(pattern.length(), None);
+    assert!(pattern.matches("computer", ""));
+    assert!(pattern.matches("compr", ""));
+    assert_eq!(Pattern::compile("c_mp_t_r").unwrap().length(), Some(8));
+    assert_eq!(
+        Pattern::compile("c*r"),
+        Err(LookupError::BadPattern(
+            "unexpected '*' in 'c*r'".to_string()
+        ))
+    );
+}
+
+#[test]
+fn test_lookup_with_classes() {
+    let index = WordIndex::from_words(vec![
+        "computer".to_string(),
+        "compster".to_string(),
+        "campster".to_string(),
+        "cxmputer".to_string(),
+        "cat".to_string(),
+        "cot".to_string(),
+        "act".to_string(),
+    ]);
+    let mut results = lookup("c[aeiou]mp[^s]t_r", &index, "").unwrap();
+    results.sort();
+    assert_eq!(results, ["computer"]);
+    let mut results = lookup("c@mp%", &index, "").unwrap();
+    results.sort();
+    assert_eq!(results, ["campster", "compster", "computer"]);
+    let mut results = lookup("#@#", &index, "").unwrap();
+    results.sort();
+    assert_eq!(results, ["cat", "cot"]);
+    // Exclusions apply to "any consonant" but not to letters listed explicitly
+    assert_eq!(lookup("c[o]#", &index, "ot").unwrap(), Vec::<String>::new());
+    assert_eq!(lookup("[co][ao]t", &index, "o").unwrap().len(), 2);
+    assert!(matches!(
+        lookup("c[aeiou", &index, ""),
+        Err(LookupError::BadPattern(_))
+    ));
+    assert!(matches!(
+        lookup("c[]t", &index, ""),
+        Err(LookupError::BadPattern(_))
+    ));
+
+    // The same syntax in -f found letters
+    let words = vec!["computer".to_string(), "compster".to_string()];
+    let results = remove_found_mismatches(&words, "c@mp[^s]t_r".to_string(), false).unwrap();
+    assert_eq!(results, ["computer"]);
+    assert_eq!(expand_found_string("computer", "c[ou]m"), "c[ou]m.....");
+}
+
 #[test]
 fn test_lookup_phrase() {
-    let words = vec![
+    let index = WordIndex::from_words(vec![
         "i feel fine".to_string(),
         "a fine mess".to_string(),
         "a dead duck".to_string(),
         "a dandelion".to_string(),
-    ];
-    let results = lookup("a d___ ___k", &words, "");
+    ]);
+    let results = lookup("a d___ ___k", &index, "").unwrap();
     assert_eq!(results.len(), 1); // should match "a dead duck"
-    let results2 = lookup("a d________", &words, "");
+    let results2 = lookup("a d________", &index, "").unwrap();
     assert_eq!(results2.len(), 1); // should only match "a dandelion", not "a dead duck"
 }
 
 #[test]
 fn test_wordle() {
-    let words = vec![
+    let index = WordIndex::from_words(vec![
         "knelt".to_string(),
         "dodge".to_string(),
         "dryer".to_string(),
         "druid".to_string(),
         "wryly".to_string(),
-    ];
+    ]);
     // We are specifically testing that wordle() finds two Ys in the results, and
     // not simply matching both against the green letter
-    let results = wordle("_ry__", &words, "", "y"); // exclude, include
+    let results = wordle("_ry__", &index, "", "y").unwrap(); // exclude, include
     assert_eq!(results.len(), 1); // should only match "wryly"
 
-    let results2 = wordle("_____", &words, "", "er");
+    let results2 = wordle("_____", &index, "", "er").unwrap();
     assert_eq!(results2.len(), 1); // should only match "dryer"
     assert_eq!(results2[0], "dryer");
 
-    let results3 = wordle("dr___", &words, "y", "");
+    let results3 = wordle("dr___", &index, "y", "").unwrap();
     assert_eq!(results3.len(), 1); // should only match "druid" because we exclude y
 
     // What if the use includes a letter that is already "green"? This signifies
     // that there's ANOTHER yellow d
-    let results4 = wordle("d____", &words, "", "d");
+    let results4 = wordle("d____", &index, "", "d").unwrap();
     assert_eq!(results4.len(), 2); // should only match "druid", and "dodge"
 }
 
 #[test]
 fn test_wordle_exclude_green() {
-    let words = vec!["adult".to_string()];
+    let index = WordIndex::from_words(vec!["adult".to_string()]);
     // Case where the user might have excluded a letter which is also in the search
     // string (i.e. is "green"). This should exclude words that have the excluded letter
     // in any position OTHER than the supplied green one.
-    let results = wordle("a___t", &words, "a", ""); // exclude, include
+    let results = wordle("a___t", &index, "a", "").unwrap(); // exclude, include
     assert_eq!(results.len(), 1); // should match
 }
 
@@ -127,6 +279,9 @@ fn test_yellow_check() {
     assert!(check_yellow_letters_exist("dryer", "__y__", "er"));
     assert!(!check_yellow_letters_exist("dryer", "__y__", "ery")); // no second y
     assert!(check_yellow_letters_exist("dryer", "d___r", "")); // no yellow letters
+    assert!(check_yellow_letters_exist("yurt", "__r_", "ut")); // four letters
+    assert!(!check_yellow_letters_exist("yurt", "__r_", "r"));
+    assert!(check_yellow_letters_exist("lantern", "l______", "nt")); // seven
 }
 
 #[test]
@@ -144,21 +299,21 @@ fn test_number_expansion() {
 
 #[test]
 fn test_regex_lookup() {
-    let words = vec![
+    let index = WordIndex::from_words(vec![
         "knelt".to_string(),
         "dodge".to_string(),
         "dryer".to_string(),
         "druid".to_string(),
         "wryly".to_string(),
-    ];
-    let mut results: Vec<String> = regex_lookup("d", &words);
+    ]);
+    let mut results: Vec<String> = regex_lookup("d", &index).unwrap();
     assert!(results.len() == 3);
-    results = regex_lookup("k", &words);
+    results = regex_lookup("k", &index).unwrap();
     assert!(results.len() == 1);
-    results = regex_lookup("..d..", &words);
+    results = regex_lookup("..d..", &index).unwrap();
     assert!(results.len() == 1);
     assert_eq!(results[0], "dodge");
-    results = regex_lookup("^..y..$", &words);
+    results = regex_lookup("^..y..$", &index).unwrap();
     assert!(results.len() == 2);
 }
 
@@ -198,14 +353,17 @@ fn test_remove_found_mismatches() {
         "abcxdef".to_string(),
     ];
     let mut found = "d...e".to_string();
-    let mut results = remove_found_mismatches(&words, found, false);
+    let mut results = remove_found_mismatches(&words, found, false).unwrap();
     assert!(results.len() == 1);
     found = "ab...ef".to_string();
-    results = remove_found_mismatches(&words, found, false);
+    results = remove_found_mismatches(&words, found, false).unwrap();
     assert!(results.len() == 2);
     found = "ab...ef".to_string();
-    results = remove_found_mismatches(&words, found, true); // ignore phrases
+    results = remove_found_mismatches(&words, found, true).unwrap(); // ignore phrases
     assert!(results.len() == 1);
+    found = "d%y%".to_string();
+    results = remove_found_mismatches(&words, found, false).unwrap();
+    assert_eq!(results, ["dryer"]);
 }
 
 #[test]
@@ -252,43 +410,31 @@ fn test_expand_numbers() {
 
 #[test]
 fn test_anagram_with_incomplete_found() {
-    let words = vec!["dodge".to_string(), "dryer".to_string()];
-    let mut anagrams: HashMap<String, Vec<usize>> = HashMap::new();
-    // Create the anagram list, note the vec contains indexes
-    // into the word list
-    anagrams.insert("derry".to_string(), vec![1usize]);
-    let results = anagram_search("ryder", &words, &anagrams);
+    let words = WordIndex::from_words(vec!["dodge".to_string(), "dryer".to_string()]);
+    let results = anagram_search("ryder", &words);
     assert_eq!(results.len(), 1); // should match "dryer"
     let found = expand_found_string("ryder", "dr");
-    let results2 = remove_found_mismatches(&results, found, true);
+    let results2 = remove_found_mismatches(&results, found, true).unwrap();
     assert_eq!(results2.len(), 1);
 }
 
 #[test]
 fn test_anagram_with_multiword_incomplete_found() {
-    let words = vec!["sumo wrestlers".to_string()];
-    let mut anagrams: HashMap<String, Vec<usize>> = HashMap::new();
-    // Create the anagram list, note the vec contains indexes
-    // into the word list
-    anagrams.insert("eelmorrssstuw".to_string(), vec![0usize]);
-    let results = anagram_search("sumsweltersor", &words, &anagrams);
+    let words = WordIndex::from_words(vec!["sumo wrestlers".to_string()]);
+    let results = anagram_search("sumsweltersor", &words);
     assert_eq!(results.len(), 1); // should match "sumo wrestlers"
     let found = expand_found_string("sumsweltersor", "sum./");
-    let results2 = remove_found_mismatches(&results, found, false);
+    let results2 = remove_found_mismatches(&results, found, false).unwrap();
     assert_eq!(results2.len(), 1);
 }
 
 #[test]
 fn test_anagram_with_multiword_incomplete_found2() {
-    let words = vec!["i suppose so".to_string()];
-    let mut anagrams: HashMap<String, Vec<usize>> = HashMap::new();
-    // Create the anagram list, note the vec contains indexes
-    // into the word list
-    anagrams.insert("eiooppsssu".to_string(), vec![0usize]);
-    let results = anagram_search("piousposse", &words, &anagrams);
+    let words = WordIndex::from_words(vec!["i suppose so".to_string()]);
+    let results = anagram_search("piousposse", &words);
     assert_eq!(results.len(), 1); // should match "i suppose so"
     let found = expand_found_string("piousposse", "i/suppose/");
-    let results2 = remove_found_mismatches(&results, found, false);
+    let results2 = remove_found_mismatches(&results, found, false).unwrap();
     assert_eq!(results2.len(), 1);
 }
 
@@ -303,3 +449,959 @@ fn test_word_contains_all_letters() {
     assert!(ui::display::word_contains_all_letters("TELLER", "RELET"));
     assert!(ui::display::word_contains_all_letters("OUTWITTING", "OUTWING"));
 }
+
+#[test]
+fn test_index_cache() {
+    let dir = std::env::temp_dir().join(format!("lookup_cache_test_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let list = dir.join("words.txt");
+    std::fs::write(&list, "listen\nsilent\nnot care\n").unwrap();
+    let file_name = list.to_str().unwrap();
+
+    let mut index = WordIndex::new();
+    file::load::full_list(&mut index, file_name).unwrap();
+    assert!(file::cache::cache_path(&list).exists());
+    // The second load should come from the cache and give the same results
+    let mut cached = WordIndex::new();
+    file::load::full_list(&mut cached, file_name).unwrap();
+    assert_eq!(cached.words(), index.words());
+    assert_eq!(anagram_search("enlist", &cached).len(), 2);
+    assert!(cached.is_phrase(2));
+    assert_eq!(cached.signature(0), &letter_counts("listen"));
+
+    // Changing the list must invalidate the cache
+    std::fs::write(&list, "listen\nsilent\ntinsel\nnot care\n").unwrap();
+    let mut rebuilt = WordIndex::new();
+    file::load::full_list(&mut rebuilt, file_name).unwrap();
+    assert_eq!(anagram_search("enlist", &rebuilt).len(), 3);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_index_cache_size() {
+    let dir = std::env::temp_dir().join(format!("lookup_cache_size_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let list = dir.join("words_1.txt");
+    std::fs::copy(
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("words_1.txt"),
+        &list,
+    )
+    .unwrap();
+    file::cache::build(&list).unwrap();
+    // The words themselves, plus about 10 bytes an entry for the anagram
+    // groups and length buckets
+    let list_size = std::fs::metadata(&list).unwrap().len();
+    let cache_size = std::fs::metadata(file::cache::cache_path(&list))
+        .unwrap()
+        .len();
+    assert!(
+        cache_size < list_size * 5 / 2,
+        "{} byte cache for a {} byte list",
+        cache_size,
+        list_size
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_errors() {
+    let words = WordIndex::from_words(vec!["plaintiff".to_string()]);
+    assert_eq!(
+        panagram("pla", &words),
+        Err(LookupError::WrongLength {
+            expected: 4,
+            actual: 3
+        })
+    );
+    assert!(matches!(
+        regex_lookup("^(unclosed", &words),
+        Err(LookupError::BadPattern(_))
+    ));
+    assert!(matches!(
+        lookup("pl*iff", &words, ""),
+        Err(LookupError::BadPattern(_))
+    ));
+    assert_eq!(
+        jumble("ABC", "X__", 0, OutputType::Json),
+        Err(LookupError::LetterNotInSource('X'))
+    );
+    let mut index = WordIndex::new();
+    assert!(matches!(
+        file::load::full_list(&mut index, "no_such_list.txt"),
+        Err(LookupError::MissingDataFile { .. })
+    ));
+}
+
+#[test]
+fn test_data_path() {
+    let dir = std::env::temp_dir().join(format!("lookup_data_test_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("data_path_test.txt"), "dryer\n").unwrap();
+    // Pass the environment in rather than setting it, as other tests run alongside
+    let dirs = file::load::data_dirs_from(|name| match name {
+        "LOOKUP_DATA_DIR" => Some(dir.clone().into_os_string()),
+        _ => None,
+    });
+    let path = file::load::data_path_in(&dirs, "data_path_test.txt").unwrap();
+    assert_eq!(path, dir.join("data_path_test.txt"));
+    let index = file::load::text_list(&path).unwrap();
+    assert_eq!(index.words(), ["dryer"]);
+    // A missing file should report everywhere we looked
+    match file::load::data_path_in(&dirs, "no_such_list.txt") {
+        Err(LookupError::MissingDataFile { searched, .. }) => {
+            assert!(searched.contains(&dir.join("no_such_list.txt")));
+            assert!(searched.contains(
+                &std::path::Path::new("/usr/share/lookup/no_such_list.txt").to_path_buf()
+            ));
+            assert_eq!(
+                searched.last().unwrap(),
+                &std::path::Path::new("./no_such_list.txt")
+            );
+        }
+        _ => panic!("expected a missing data file error"),
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+// Runs the lookup command with only the data files in `dir`
+fn run_lookup(dir: &std::path::Path, args: &[&str]) -> std::process::Output {
+    std::process::Command::new(env!("CARGO_BIN_EXE_lookup"))
+        .args(args)
+        .current_dir(dir)
+        .env("LOOKUP_DATA_DIR", dir)
+        .env("XDG_DATA_HOME", dir)
+        .env("XDG_DATA_DIRS", dir)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_without_words_3() {
+    let dir = std::env::temp_dir().join(format!("lookup_obscurity_test_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("words_1.txt"), "computer\n").unwrap();
+    assert_eq!(
+        file::load::default_obscurity_in(std::slice::from_ref(&dir)),
+        1
+    );
+    std::fs::write(dir.join("words_2.txt"), "compater\ncomputer\n").unwrap();
+    std::fs::write(dir.join("phrases.txt"), "come to pass\n").unwrap();
+    assert_eq!(
+        file::load::default_obscurity_in(std::slice::from_ref(&dir)),
+        2
+    );
+
+    // With no -o, the biggest list there is gets searched
+    let output = run_lookup(&dir, &["c_mp_t_r"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("compater") && stdout.contains("computer"));
+    // Asking for the missing list is still an error
+    assert_eq!(
+        run_lookup(&dir, &["-o", "3", "c_mp_t_r"]).status.code(),
+        Some(10)
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_multi_anagram_unquoted() {
+    let dir = std::env::temp_dir().join(format!("lookup_unquoted_test_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("words_1.txt"), "brandenburg\ngate\nbead\n").unwrap();
+    std::fs::write(dir.join("phrases.txt"), "come to pass\n").unwrap();
+    // Several words are letters to anagram, not a phrase to look up
+    for args in [
+        ["-M", "beget grand urban"].as_slice(),
+        &["-M", "beget", "grand", "urban"],
+    ] {
+        let output = run_lookup(&dir, args);
+        assert!(output.status.success());
+        assert!(String::from_utf8(output.stdout)
+            .unwrap()
+            .contains("brandenburg gate"));
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_multi_anagram() {
+    let words = WordIndex::from_words(vec![
+        "brandenburg".to_string(),
+        "gate".to_string(),
+        "bead".to_string(),
+        "grunt".to_string(),
+        "grange".to_string(),
+        "burnt".to_string(),
+        "brant".to_string(),
+        "grunge".to_string(),
+        "i suppose so".to_string(),
+    ]);
+    let results = multi_anagram("begetgrandurban", &words, &[11, 4], "", 3).unwrap();
+    assert_eq!(results, vec!["brandenburg gate"]);
+    // The enumeration can come from the found letters instead
+    let results = multi_anagram("begetgrandurban", &words, &[], "b.a.......g/...e", 3).unwrap();
+    assert_eq!(results, vec!["brandenburg gate"]);
+    // With no enumeration any combination of words is allowed, which are
+    // given in word list order
+    let mut results = multi_anagram("begetgrandurban", &words, &[], "", 3).unwrap();
+    results.sort();
+    let expected = vec!["bead brant grunge", "bead grange burnt", "brandenburg gate"];
+    assert_eq!(results, expected);
+    // Phrases in the list are matched as they are
+    let results = multi_anagram("piousposse", &words, &[1, 7, 2], "", 3).unwrap();
+    assert_eq!(results, vec!["i suppose so"]);
+    assert!(matches!(
+        multi_anagram("begetgrandurban", &words, &[11, 5], "", 3),
+        Err(LookupError::WrongLength { .. })
+    ));
+    assert!(matches!(
+        multi_anagram("begetgrandurban", &words, &[11, 4], "..........é...", 3),
+        Err(LookupError::BadPattern(_))
+    ));
+    assert_eq!(parse_enumeration("11,4").unwrap(), vec![11, 4]);
+}
+
+#[test]
+fn test_hidden_words() {
+    let words = WordIndex::from_words(vec![
+        "thai".to_string(),
+        "fire".to_string(),
+        "aim".to_string(),
+        "level".to_string(),
+        "sea bream".to_string(),
+    ]);
+    let results = hidden_words("birthaim", &words, 3);
+    assert_eq!(results.len(), 2);
+    assert_eq!(
+        results[0],
+        HiddenWord {
+            word: "thai".to_string(),
+            start: 3,
+            end: 7,
+            step: 1,
+            reversed: false
+        }
+    );
+    assert_eq!(results[1].word, "aim");
+    // Reversed, and spaces are ignored
+    let results = hidden_words("beer iffy", &words, 3);
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].word, "fire");
+    assert!(results[0].reversed);
+    assert_eq!((results[0].start, results[0].end), (2, 6));
+    assert!(results[0].spans_words("beer iffy"));
+    assert!(!hidden_words("birthaim", &words, 3)[0].spans_words("birthaim"));
+    // Palindromes are only found once, and phrases are found too
+    assert_eq!(hidden_words("bevel level", &words, 3).len(), 1);
+    assert_eq!(
+        hidden_words("those abreaming", &words, 3)[0].word,
+        "sea bream"
+    );
+    assert_eq!(hidden_words("birthaim", &words, 4).len(), 1);
+}
+
+#[test]
+fn test_regular_words() {
+    let words = WordIndex::from_words(vec![
+        "bones".to_string(),
+        "sob".to_string(),
+        "tin".to_string(),
+        "rose".to_string(),
+    ]);
+    let results = regular_words("bro sneers", &words, 3);
+    let found: Vec<(&str, usize, bool)> = results
+        .iter()
+        .map(|r| (r.word.as_str(), r.step, r.reversed))
+        .collect();
+    assert!(found.contains(&("bones", 2, false)));
+    // Every other letter of "bao as", backwards
+    let results = regular_words("bao as", &words, 3);
+    assert!(results.iter().any(|r| r.word == "sob" && r.reversed));
+    let bones = regular_words("bro sneers", &words, 3);
+    assert_eq!(
+        bones[0].positions().collect::<Vec<usize>>(),
+        vec![0, 2, 4, 6, 8]
+    );
+    // Every third letter
+    let results = regular_words("twain on", &words, 3);
+    assert_eq!(results.len(), 1);
+    assert_eq!((results[0].word.as_str(), results[0].step), ("tin", 3));
+    // A word hidden as a whole isn't a regular pattern
+    assert!(regular_words("xrosex", &words, 3).is_empty());
+}
+
+#[test]
+fn test_containers() {
+    let words = WordIndex::from_words(vec![
+        "paint".to_string(),
+        "pie".to_string(),
+        "patin".to_string(),
+        "de facto".to_string(),
+    ]);
+    let results = containers(&["in".to_string()], &["pat".to_string()], &words);
+    assert_eq!(
+        results,
+        vec![Wordplay {
+            answer: "paint".to_string(),
+            explanation: "in in pat: pa(in)t".to_string()
+        }]
+    );
+    // Either part can go inside the other, and answers can be phrases
+    let results = containers(
+        &["deo".to_string(), "pie".to_string()],
+        &["fact".to_string()],
+        &words,
+    );
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].answer, "de facto");
+    assert_eq!(results[0].explanation, "fact in deo: de(fact)o");
+}
+
+#[test]
+fn test_remove_letters() {
+    assert_eq!(
+        remove_letters("aldershotcafedemolition", "aldershotdemolition").unwrap(),
+        "cafe"
+    );
+    assert_eq!(remove_letters("Beer iffy", "fire").unwrap(), "befy");
+    assert_eq!(
+        remove_letters("cafe", "face off"),
+        Err(LookupError::LetterNotInSource('o'))
+    );
+    // Removing from a shorter string than the letters
+    let remaining = remove_letters("listen", "tin").unwrap();
+    let words = WordIndex::from_words(vec!["els".to_string(), "sel".to_string()]);
+    assert_eq!(anagram_search(&remaining, &words), vec!["els", "sel"]);
+}
+
+#[test]
+fn test_charades() {
+    let words = WordIndex::from_words(vec![
+        "race".to_string(),
+        "pace".to_string(),
+        "ace".to_string(),
+        "rat race".to_string(),
+    ]);
+    let parts = vec![
+        vec!["river".to_string(), "r".to_string(), "po".to_string()],
+        vec!["ace".to_string()],
+    ];
+    let results = charades(&parts, &words);
+    assert_eq!(
+        results,
+        vec![Wordplay {
+            answer: "race".to_string(),
+            explanation: "r (river) + ace".to_string()
+        }]
+    );
+    // Candidates are joined in order, and can make phrases
+    let parts = vec![
+        vec!["rat".to_string()],
+        vec!["rac".to_string(), "ra".to_string()],
+        vec!["e".to_string(), "ce".to_string()],
+    ];
+    let results: Vec<String> = charades(&parts, &words)
+        .into_iter()
+        .map(|w| w.explanation)
+        .collect();
+    assert_eq!(results, vec!["rat + rac + e", "rat + ra (rac) + ce (e)"]);
+}
+
+#[test]
+fn test_indicators() {
+    // Uses the indicators.txt and abbreviations.txt in the current directory
+    let mut results = vec![];
+    file::load::indicators(&mut results, "about").unwrap();
+    assert_eq!(
+        results,
+        vec![
+            IndicatorType::Anagram,
+            IndicatorType::Container,
+            IndicatorType::Reversal
+        ]
+    );
+    // Indicators can be phrases, and must match the whole thing
+    results.clear();
+    file::load::indicators(&mut results, "mixed up").unwrap();
+    assert_eq!(results, vec![IndicatorType::Anagram]);
+    results.clear();
+    file::load::indicators(&mut results, "mix").unwrap();
+    assert!(results.is_empty());
+
+    let mut abbreviations = vec![];
+    file::load::abbreviations(&mut abbreviations, "sailor").unwrap();
+    assert_eq!(abbreviations, vec!["ab", "tar", "os", "salt"]);
+    assert_eq!(
+        IndicatorType::from_name("Homophone"),
+        Some(IndicatorType::Homophone)
+    );
+    assert_eq!(IndicatorType::Regular.to_string(), "regular");
+}
+
+#[test]
+fn test_analyse_clue() {
+    let (words, enumeration) = parse_clue("Flame: beer iffy about (4)").unwrap();
+    assert_eq!(words, ["flame", "beer", "iffy", "about"]);
+    assert_eq!(enumeration, [4]);
+    assert!(parse_clue("Flame beer iffy about").is_err());
+
+    let indicators = find_indicators(&words, &file::load::indicator_map().unwrap());
+    assert!(indicators.contains(&ClueIndicator {
+        start: 2,
+        end: 3,
+        kind: IndicatorType::Anagram
+    }));
+    let index = WordIndex::from_words(vec![
+        "fire".to_string(),
+        "rife".to_string(),
+        "bree".to_string(),
+    ]);
+    let thesaurus = KeyedEntries::from([(
+        "flame".to_string(),
+        vec!["fire".to_string(), "blaze".to_string()],
+    )]);
+    let results = analyse_clue(
+        &words,
+        &enumeration,
+        &indicators,
+        &thesaurus,
+        &KeyedEntries::new(),
+        &index,
+    );
+    assert_eq!(results[0].answer, "fire");
+    assert_eq!(
+        results[0].explanation,
+        "hidden reversed in 'beer iffy' (indicated by 'about'); definition 'flame'"
+    );
+    // "iffy" can also mean an anagram of "beer", but it's less likely
+    assert!(results.iter().any(|r| r.answer == "bree"));
+    assert!(!results.iter().any(|r| r.answer == "rife"));
+
+    // A word hidden across "bobcat ate" is likelier than one inside "bobcat"
+    let (words, enumeration) = parse_clue("Feline: some bobcat ate (3)").unwrap();
+    let indicators = find_indicators(&words, &file::load::indicator_map().unwrap());
+    let index = WordIndex::from_words(vec!["cat".to_string(), "tat".to_string()]);
+    let empty = KeyedEntries::new();
+    let results = analyse_clue(&words, &enumeration, &indicators, &empty, &empty, &index);
+    let answers: Vec<&str> = results.iter().map(|r| r.answer.as_str()).collect();
+    assert_eq!(answers, ["tat", "cat"]);
+}
+
+#[test]
+fn test_enumeration_patterns() {
+    assert_eq!(parse_enumeration("(5,3-4)").unwrap(), [5, 3, 4]);
+    assert_eq!(parse_enumeration("(2'1,4)").unwrap(), [3, 4]);
+    assert!(parse_enumeration("(2'',4)").is_err());
+    assert_eq!(enumeration("a dead duck"), "1,4,4");
+    assert_eq!(enumeration("jack-in-the-box"), "4-2-3-3");
+    assert_eq!(enumeration("o'clock"), "1'5");
+    assert!(matches_enumeration("jack-in-the-box", &[4, 2, 3, 3]));
+    assert!(matches_enumeration("o'clock", &[6]));
+    assert_eq!(
+        expand_numbers(&expand_enumeration("(5,3-4)")),
+        "...../...-...."
+    );
+
+    let index = WordIndex::from_words(vec![
+        "jack in the box".to_string(),
+        "jack-in-the-box".to_string(),
+        "o'clock".to_string(),
+        "oclock".to_string(),
+        "anti clockwise".to_string(),
+        "anticlockwise".to_string(),
+    ]);
+    let mut results = lookup(&expand_numbers("4-2-3-3"), &index, "").unwrap();
+    results.sort();
+    assert_eq!(results, ["jack in the box", "jack-in-the-box"]);
+    // A hyphen has to be a word break
+    let results = lookup("anti-......ise", &index, "").unwrap();
+    assert_eq!(results, ["anti clockwise"]);
+    let results = lookup("jack/in/the/box", &index, "").unwrap();
+    assert_eq!(results.len(), 2);
+    // Apostrophes are optional both ways
+    let mut results = lookup(&expand_numbers("1'5"), &index, "").unwrap();
+    results.sort();
+    assert_eq!(results, ["o'clock", "oclock"]);
+    assert_eq!(lookup("oc....", &index, "").unwrap().len(), 2);
+
+    // ...and in -f found letters
+    let words = vec!["jack in the box".to_string(), "jackinthebox".to_string()];
+    let results = remove_found_mismatches(&words, "j3-2-t2-b2".to_string(), false).unwrap();
+    assert_eq!(results, ["jack in the box"]);
+    let results = remove_found_mismatches(&words, "(4,2,3,3)".to_string(), false).unwrap();
+    assert_eq!(results, ["jack in the box"]);
+    assert_eq!(expand_found_string("jackinthebox", "j3-2"), "j...-........");
+    assert_eq!(expand_found_string("../...", "i/t"), "i/t...");
+}
+
+#[test]
+fn test_variable_lookup() {
+    let index = WordIndex::from_words(vec![
+        "sets".to_string(),
+        "pops".to_string(),
+        "that".to_string(),
+        "tilt".to_string(),
+        "teat".to_string(),
+        "eye to eye".to_string(),
+    ]);
+    let mut results = variable_lookup("1231", &index).unwrap();
+    results.sort();
+    assert_eq!(results, ["sets", "teat", "that", "tilt"]);
+    assert_eq!(variable_lookup("ABCA", &index).unwrap().len(), 4);
+    // Wildcards can be anything, even the same as a variable
+    let mut results = variable_lookup("1_2_", &index).unwrap();
+    results.sort();
+    assert_eq!(results, ["sets", "teat", "that", "tilt"]);
+    assert_eq!(variable_lookup("1_1_", &index).unwrap(), ["pops"]);
+    // Variables can't be a letter given in the pattern
+    assert_eq!(variable_lookup("s1t_", &index).unwrap(), ["sets"]);
+    assert!(variable_lookup("s1t2", &index).unwrap().is_empty());
+    let mut results = variable_lookup("t1At", &index).unwrap();
+    results.sort();
+    assert_eq!(results, ["teat", "that", "tilt"]);
+    // Phrases too
+    assert_eq!(
+        variable_lookup("121/34/121", &index).unwrap(),
+        ["eye to eye"]
+    );
+    assert!(matches!(
+        variable_lookup("12*", &index),
+        Err(LookupError::BadPattern(_))
+    ));
+}
+
+// Reads one of the data files in the repository into an index
+fn data_file_index(file_name: &str) -> WordIndex {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(file_name);
+    let text = std::fs::read_to_string(path).unwrap();
+    WordIndex::from_words(text.lines().map(|line| line.to_string()).collect())
+}
+
+#[test]
+fn test_codeword() {
+    assert_eq!(letter_pattern("that"), "ABCA");
+    assert_eq!(letter_pattern("eye to eye"), "ABA CD ABA");
+
+    let codeword = Codeword::parse(
+        "1 2 3 4 5
+         6 # # # 3
+         7 8 1 5 2
+         5 # # # 5
+         1 2 5 5 4
+         6=h",
+    )
+    .unwrap();
+    assert_eq!(codeword.cells[1], [Some(6), None, None, None, Some(3)]);
+    assert_eq!(codeword.given, HashMap::from([(6, 'h')]));
+    let words = codeword.words();
+    assert_eq!(words.len(), 5);
+    assert_eq!(words[0], [1, 2, 3, 4, 5]);
+    assert_eq!(words[3], [1, 6, 7, 5, 1]);
+
+    let index = WordIndex::from_words(
+        [
+            "stare", "onset", "steer", "shoes", "eater", "shies", "inset", "aster",
+        ]
+        .iter()
+        .map(|w| w.to_string())
+        .collect(),
+    );
+    let key = solve_substitution(&words, &codeword.given, &index, &HashMap::new()).unwrap();
+    let decoded: Vec<String> = words
+        .iter()
+        .map(|word| word.iter().map(|n| key[n]).collect())
+        .collect();
+    assert_eq!(decoded, ["stare", "onset", "steer", "shoes", "eater"]);
+    // Different numbers are always different letters
+    let given = HashMap::from([(6, 'h'), (7, 'h')]);
+    assert_eq!(
+        solve_substitution(&words, &given, &index, &HashMap::new()),
+        None
+    );
+
+    assert!(Codeword::parse("1 2 27").is_err());
+    assert!(Codeword::parse("1 2 3\n2=ab").is_err());
+    assert_eq!(
+        parse_key("G=t, h=E").unwrap(),
+        [("G".to_string(), 't'), ("h".to_string(), 'e')]
+    );
+}
+
+#[test]
+fn test_codeword_grid() {
+    let index = data_file_index("words_1.txt");
+    let uses = word_uses(&data_file_index("phrases.txt"));
+    let codeword = Codeword::parse(
+        "23 20 10 17 17  8 24  # 21 10 18  8 12
+         20  #  7  #  3  #  3  #  8  #  1  #  8
+         10 24 10 12 16  # 23  1 24  1  3 12  4
+         16  # 12  #  6  # 14  # 23  # 15  #  8
+          6 10 18 18  8 12  5  #  1 18 20  8 12
+          8  #  #  # 12  #  #  # 16  #  #  #  2
+         12 26 17 15  4  # 18 20  8 10 18 12  8
+          #  #  3  #  #  # 20  #  #  #  8  #  #
+         16 10 23 20 26 17  8  # 25  8 10  4 18
+         10  # 24  #  4  # 12  #  1  # 23  # 20
+         14 17  8  8  4  # 10 17  1 18 20  8 12
+          8  # 10  #  3  #  7  # 24  #  8  #  1
+          4  3 12 15  8 12  5  #  4 18 12 10 21
+         8=e, 4=s",
+    )
+    .unwrap();
+    let words = codeword.words();
+    assert_eq!(words.len(), 28);
+    let key = solve_substitution(&words, &codeword.given, &index, &uses).unwrap();
+    let decoded: Vec<String> = words
+        .iter()
+        .map(|word| word.iter().map(|n| key[n]).collect())
+        .collect();
+    assert_eq!(
+        decoded,
+        [
+            "channel", "water", "alarm", "colours", "battery", "other", "rings", "theatre",
+            "machine", "feast", "knees", "another", "surgery", "straw", "chamber", "makes",
+            "apart", "nuclear", "numbers", "issue", "lucky", "therapy", "welcome", "fools",
+            "tough", "teacher", "reserve", "throw"
+        ]
+    );
+}
+
+#[test]
+fn test_cryptogram() {
+    let cryptogram = Cryptogram::new("Gsv xzg, gsv well-mzn'h xzg");
+    let words = cryptogram.words();
+    assert_eq!(words.len(), 6);
+    assert_eq!(words[1], [23, 25, 6]);
+    // The apostrophe is ignored
+    assert_eq!(words[4], [12, 25, 13, 7]);
+    assert_eq!(
+        Cryptogram::parse_given("g=T").unwrap(),
+        HashMap::from([(6, 't')])
+    );
+    assert!(Cryptogram::parse_given("GS=t").is_err());
+
+    let index = WordIndex::from_words(
+        ["the", "cat", "man's", "sat", "hat", "on", "mat"]
+            .iter()
+            .map(|w| w.to_string())
+            .collect(),
+    );
+    // "well" isn't in the index, so has to be left unsolved
+    let uses = HashMap::new();
+    assert_eq!(
+        solve_substitution(&words, &HashMap::new(), &index, &uses),
+        None
+    );
+    let given = Cryptogram::parse_given("x=c").unwrap();
+    let solution = partial_substitution(&words, &given, &index, &uses).unwrap();
+    assert_eq!(solution.unsolved, [3]);
+    let decoded: Vec<String> = words
+        .iter()
+        .map(|word| {
+            word.iter()
+                .map(|n| solution.key.get(n).unwrap_or(&'?'))
+                .collect()
+        })
+        .collect();
+    assert_eq!(decoded, ["the", "cat", "the", "????", "mans", "cat"]);
+}
+
+#[test]
+fn test_cryptogram_pangram() {
+    let index = data_file_index("words_1.txt");
+    let uses = word_uses(&data_file_index("phrases.txt"));
+    assert!(uses["the"] > uses["quick"]);
+    let cryptogram = Cryptogram::new("GSV JFRXP YILDM ULC QFNKH LEVI GSV OZAB WLT");
+    let words = cryptogram.words();
+    // On its own the pangram has other answers using words as common, e.g.
+    // "the quick wrong job dumps over the lazy fox", but the letters for
+    // "quick" and "dog" are enough to settle the rest
+    let given = Cryptogram::parse_given("J=q, W=d").unwrap();
+    let key = solve_substitution(&words, &given, &index, &uses).unwrap();
+    let decoded: Vec<String> = words
+        .iter()
+        .map(|word| word.iter().map(|n| key[n]).collect())
+        .collect();
+    assert_eq!(
+        decoded.join(" "),
+        "the quick brown fox jumps over the lazy dog"
+    );
+}
+
+#[test]
+fn test_cryptogram_example() {
+    // The example in the README and --help
+    let index = data_file_index("words_1.txt");
+    let uses = word_uses(&data_file_index("phrases.txt"));
+    let cryptogram = Cryptogram::new("ZM ZKKOV Z WZB PVVKH GSV WLXGLI ZDZB");
+    let words = cryptogram.words();
+    let solution = partial_substitution(&words, &HashMap::new(), &index, &uses).unwrap();
+    assert!(solution.unsolved.is_empty());
+    let decoded: Vec<String> = words
+        .iter()
+        .map(|word| word.iter().map(|n| solution.key[n]).collect())
+        .collect();
+    assert_eq!(decoded.join(" "), "an apple a day keeps the doctor away");
+}
+
+#[test]
+fn test_wordle_feedback() {
+    use Tile::*;
+    assert_eq!(
+        wordle_feedback("crane", "knelt").unwrap(),
+        [Grey, Grey, Grey, Yellow, Yellow]
+    );
+    // Only one E is yellow, as the other E in the answer is green
+    assert_eq!(
+        wordle_feedback("eerie", "tepee").unwrap(),
+        [Yellow, Green, Grey, Grey, Green]
+    );
+    // A green takes priority over an earlier yellow
+    assert_eq!(
+        wordle_feedback("speed", "abide").unwrap(),
+        [Grey, Grey, Yellow, Grey, Yellow]
+    );
+    assert_eq!(
+        wordle_feedback("llama", "hello").unwrap(),
+        [Yellow, Yellow, Grey, Grey, Grey]
+    );
+    assert!(matches!(
+        wordle_feedback("crane", "cranes"),
+        Err(LookupError::WrongLength { .. })
+    ));
+    let long = "a".repeat(20);
+    assert!(matches!(
+        wordle_feedback(&long, &long),
+        Err(LookupError::WrongLength { .. })
+    ));
+}
+
+#[test]
+fn test_suggest_guesses() {
+    let answers: Vec<String> = ["brunt", "front", "grunt", "print", "trunk"]
+        .iter()
+        .map(|w| w.to_string())
+        .collect();
+    let mut guesses = answers.clone();
+    guesses.push("daddy".to_string());
+    let suggestions = suggest_guesses(&answers, &guesses, 3, 0);
+    assert_eq!(suggestions.len(), 3);
+    // Each of these splits the answers into groups of 2, 1, 1 and 1
+    assert_eq!(suggestions[0].guess, "brunt");
+    assert_eq!(suggestions[1].guess, "grunt");
+    assert!((suggestions[0].entropy - 1.922).abs() < 0.001);
+    assert!((suggestions[0].expected_remaining - 1.4).abs() < 0.001);
+    assert!(suggestions[0].possible_answer);
+
+    // A guess that can't be the answer can still be the most useful
+    let answers: Vec<String> = ["bills", "fills", "hills", "mills"]
+        .iter()
+        .map(|w| w.to_string())
+        .collect();
+    let mut guesses = answers.clone();
+    guesses.push("bumph".to_string());
+    let suggestions = suggest_guesses(&answers, &guesses, 1, 0);
+    assert_eq!(suggestions[0].guess, "bumph");
+    assert_eq!(suggestions[0].entropy, 2.0);
+    assert!(!suggestions[0].possible_answer);
+    // Only the guesses splitting the answers most evenly are scored, here
+    // one of the answers and "bumph"
+    let suggestions = suggest_guesses(&answers, &guesses, 5, 1);
+    assert_eq!(suggestions.len(), 2);
+    assert_eq!(suggestions[0].guess, "bumph");
+}
+
+#[test]
+fn test_wordle_constraints() {
+    use Tile::*;
+    let feedback = |constraints: &mut WordleConstraints, feedback: &str| {
+        let (guess, tiles) = WordleConstraints::parse_feedback(feedback).unwrap();
+        constraints.add_feedback(&guess, &tiles).unwrap();
+    };
+    assert_eq!(
+        WordleConstraints::parse_feedback("Crane:gy..B").unwrap(),
+        ("crane".to_string(), vec![Green, Yellow, Grey, Grey, Grey])
+    );
+    assert!(WordleConstraints::parse_feedback("crane").is_err());
+    assert!(WordleConstraints::parse_feedback("crane:gy.").is_err());
+    assert!(WordleConstraints::parse_feedback("crane:gyz..").is_err());
+
+    let mut constraints = WordleConstraints::new(5);
+    feedback(&mut constraints, "crane:..gy.");
+    assert!(constraints.matches("snail"));
+    assert!(constraints.matches("plain"));
+    // N can't be where it was yellow
+    assert!(!constraints.matches("shank"));
+    assert!(!constraints.matches("chain"));
+    assert!(!constraints.matches("sprain"));
+
+    // Three Es, none of them in the middle
+    let mut constraints = WordleConstraints::new(5);
+    feedback(&mut constraints, "eerie:yg..g");
+    assert_eq!(constraints.min_counts[&'e'], 3);
+    assert!(!constraints.max_counts.contains_key(&'e'));
+    assert!(constraints.matches("tepee"));
+    assert!(!constraints.matches("fewer"));
+    assert!(!constraints.matches("eerie"));
+    // A yellow E and a grey E means exactly one E, and not in either place
+    let mut constraints = WordleConstraints::new(5);
+    feedback(&mut constraints, "speed:..y.y");
+    assert_eq!(constraints.min_counts[&'e'], 1);
+    assert_eq!(constraints.max_counts[&'e'], 1);
+    assert!(constraints.matches("abide"));
+    assert!(!constraints.matches("eased"));
+    assert!(!constraints.matches("geode"));
+
+    // Each answer fits the feedback it gives, and no other
+    let words = [
+        "tepee", "eerie", "geese", "abide", "speed", "llama", "hello",
+    ];
+    for guess in words {
+        for answer in words {
+            let mut constraints = WordleConstraints::new(5);
+            constraints
+                .add_feedback(guess, &wordle_feedback(guess, answer).unwrap())
+                .unwrap();
+            for word in words {
+                assert_eq!(
+                    constraints.matches(word),
+                    wordle_feedback(guess, word) == wordle_feedback(guess, answer),
+                    "{} for {} then {}",
+                    guess,
+                    answer,
+                    word
+                );
+            }
+        }
+    }
+
+    let mut constraints = WordleConstraints::new(5);
+    feedback(&mut constraints, "crane:..gy.");
+    // Grey letters can still be used in hard mode, but the green A must stay
+    // put and the N be used somewhere
+    assert!(constraints.allows_in_hard_mode("crane"));
+    assert!(constraints.allows_in_hard_mode("knave"));
+    assert!(!constraints.allows_in_hard_mode("snips"));
+    assert!(!constraints.allows_in_hard_mode("nasal"));
+    assert!(WordleConstraints::new(5).allows_in_hard_mode("noahs"));
+
+    let mut constraints = WordleConstraints::new(5);
+    assert!(constraints.add_feedback("bread", &[Grey; 4]).is_err());
+    assert!(constraints.add_feedback("breads", &[Grey; 6]).is_err());
+}
+
+#[test]
+fn test_wordle_variants() {
+    use Tile::*;
+    let index = WordIndex::from_words(
+        ["kitchen", "kitten", "mitten", "bitten"]
+            .iter()
+            .map(|w| w.to_string())
+            .collect(),
+    );
+    assert_eq!(
+        wordle("_itt_n", &index, "b", "").unwrap(),
+        ["kitten", "mitten"]
+    );
+    assert_eq!(wordle("k______", &index, "", "").unwrap(), ["kitchen"]);
+    assert!(matches!(
+        wordle("___", &index, "", ""),
+        Err(LookupError::WrongLength {
+            expected: 4,
+            actual: 3
+        })
+    ));
+    assert!(wordle("____________", &index, "", "").is_err());
+
+    let (guess, boards) = WordleConstraints::parse_boards_feedback("Crane:gy..b,,ggggg").unwrap();
+    assert_eq!(guess, "crane");
+    assert_eq!(
+        boards,
+        [
+            Some(vec![Green, Yellow, Grey, Grey, Grey]),
+            None,
+            Some(vec![Green; 5])
+        ]
+    );
+    assert!(WordleConstraints::parse_boards_feedback("crane:gy..b,gggg").is_err());
+    assert!(WordleConstraints::parse_feedback("crane:gy..b,ggggg").is_err());
+
+    let mut constraints = WordleConstraints::new(5);
+    assert!(!constraints.is_solved());
+    constraints
+        .add_feedback("crane", &boards[2].clone().unwrap())
+        .unwrap();
+    assert!(constraints.is_solved());
+
+    // The information from each board adds up
+    let board = |words: &[&str]| -> Vec<String> { words.iter().map(|w| w.to_string()).collect() };
+    let boards = [
+        board(&["bills", "fills", "hills", "mills"]),
+        board(&["brunt", "front", "grunt", "print", "trunk"]),
+    ];
+    let guesses = board(&["bumph", "daddy"]);
+    let suggestions = suggest_guesses_for_boards(&boards, &guesses, 2, 0);
+    assert_eq!(suggestions[0].guess, "bumph");
+    let single = |answers: &[String]| suggest_guesses(answers, &guesses[..1], 1, 0)[0].entropy;
+    assert_eq!(
+        suggestions[0].entropy,
+        single(&boards[0]) + single(&boards[1])
+    );
+    assert!(!suggestions[0].possible_answer);
+    assert_eq!(suggestions[1].entropy, 0.0);
+    assert_eq!(suggestions[1].expected_remaining, 9.0);
+}
+
+#[test]
+fn test_letterboxed() {
+    let sides = parse_letterboxed("LIA/TGK/ROF/NEY").unwrap();
+    assert_eq!(sides, ["lia", "tgk", "rof", "ney"]);
+    assert_eq!(parse_letterboxed("lia tgk,rof/ney").unwrap(), sides);
+    assert!(parse_letterboxed("LIA/TGK/ROF/NEA").is_err());
+    assert!(parse_letterboxed("LIA/T?K").is_err());
+    assert!(parse_letterboxed("LIA").is_err());
+
+    let index = WordIndex::from_words(
+        [
+            "gratifyingly",
+            "yoke",
+            "yokel",
+            "keno",
+            "flag",
+            "tail",
+            "ok",
+            "rat",
+        ]
+        .iter()
+        .map(|w| w.to_string())
+        .collect(),
+    );
+    // "keno" and "flag" have two letters from the same side together, "tail"
+    // has three, and "ok" is too short
+    assert_eq!(
+        letterboxed_words(&sides, &index),
+        ["gratifyingly", "yoke", "yokel", "rat"]
+    );
+    assert_eq!(
+        letterboxed(&sides, &index, 3),
+        [vec!["gratifyingly", "yoke"], vec!["gratifyingly", "yokel"]]
+    );
+    assert!(letterboxed(&sides, &index, 1).is_empty());
+
+    // With no two word answer, the shortest three word ones are found
+    let index = WordIndex::from_words(
+        ["gratify", "yoke", "eloin", "yokel", "loin", "elan"]
+            .iter()
+            .map(|w| w.to_string())
+            .collect(),
+    );
+    assert_eq!(
+        letterboxed(&sides, &index, 3),
+        [
+            vec!["gratify", "yoke", "eloin"],
+            vec!["gratify", "yokel", "loin"]
+        ]
+    );
+    assert!(letterboxed(&sides, &index, 2).is_empty());
+}